@@ -166,6 +166,21 @@ impl CacheKeyHasher {
         value.cache_key(&mut hasher);
         hasher.finish()
     }
+
+    /// Add another component to the key.
+    ///
+    /// Components are framed (strings are length-prefixed), so `("rails", "7.1.0")` and
+    /// `("rails7", ".1.0")` give different digests.
+    #[must_use]
+    pub fn push<T: CacheKey>(mut self, value: T) -> Self {
+        value.cache_key(&mut self);
+        self
+    }
+
+    /// The digest of all the components pushed so far, formatted like [`cache_digest`].
+    pub fn digest(&self) -> String {
+        format!("{:x}", self.finish())
+    }
 }
 
 impl Hasher for CacheKeyHasher {
@@ -267,8 +282,7 @@ impl_cache_key_tuple! { T B C D E F G H I J K L }
 
 /// Generate a cache digest for any cacheable value
 pub fn cache_digest<T: CacheKey>(value: T) -> String {
-    let hash = CacheKeyHasher::hash_one(value);
-    format!("{hash:x}")
+    CacheKeyHasher::new().push(value).digest()
 }
 
 #[cfg(test)]
@@ -285,6 +299,34 @@ mod tests {
         assert_ne!(digest1, digest3);
     }
 
+    #[test]
+    fn test_multi_component_digest() {
+        let rails = CacheKeyHasher::new().push("rails").push("7.1.0").digest();
+        let rails7 = CacheKeyHasher::new().push("rails7").push(".1.0").digest();
+        assert_ne!(rails, rails7);
+        assert_ne!(
+            cache_digest(("rails", "7.1.0")),
+            cache_digest(("rails7", ".1.0"))
+        );
+
+        // Pushing components one at a time is the same as hashing them as a tuple.
+        assert_eq!(rails, cache_digest(("rails", "7.1.0")));
+
+        let platform = CacheKeyHasher::new()
+            .push("nokogiri")
+            .push("1.18.10")
+            .push(Some("arm64-darwin"))
+            .push("rubygems.org")
+            .digest();
+        let no_platform = CacheKeyHasher::new()
+            .push("nokogiri")
+            .push("1.18.10")
+            .push(None::<&str>)
+            .push("rubygems.org")
+            .digest();
+        assert_ne!(platform, no_platform);
+    }
+
     #[test]
     fn test_utf8_path_cache_key() {
         let path1 = Utf8Path::new("/usr/local/bin/ruby");
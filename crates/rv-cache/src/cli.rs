@@ -23,6 +23,18 @@ pub struct CacheArgs {
     /// Defaults to platform-specific cache directory or `~/.cache/rv` on Unix systems.
     #[arg(global = true, long, env = "RV_CACHE_DIR")]
     pub cache_dir: Option<Utf8PathBuf>,
+
+    /// Where to create the temporary cache directory used by `--no-cache`.
+    ///
+    /// Defaults to the system temporary directory.
+    #[arg(global = true, long, env = "RV_CACHE_TMPDIR")]
+    pub cache_tmpdir: Option<Utf8PathBuf>,
+
+    /// How many threads to remove cache entries on, when cleaning or pruning the cache.
+    ///
+    /// Defaults to the number of available CPUs.
+    #[arg(global = true, long, env = "RV_CACHE_JOBS")]
+    pub cache_jobs: Option<usize>,
 }
 
 impl CacheArgs {
@@ -34,23 +46,35 @@ impl CacheArgs {
 impl Cache {
     /// Create a cache from settings, preferring in order:
     ///
-    /// 1. A temporary cache directory, if the user requested `--no-cache`.
+    /// 1. A temporary cache directory, if the user requested `--no-cache`. It's created inside
+    ///    `temp_parent` if given, or the system temporary directory otherwise.
     /// 2. The specific cache directory specified by the user via `--cache-dir` or `RV_CACHE_DIR`.
     /// 3. The system-appropriate cache directory.
     ///
+    /// Entries are removed on `jobs` threads, or one per available CPU if that isn't given.
+    ///
     /// Returns an absolute cache dir.
     pub fn from_settings(
         no_cache: bool,
         cache_dir: Option<&Utf8PathBuf>,
+        temp_parent: Option<&Utf8PathBuf>,
+        jobs: Option<usize>,
     ) -> Result<Self, io::Error> {
-        if no_cache {
-            Self::temp()
+        let cache = if no_cache {
+            match temp_parent {
+                Some(parent) => Self::temp_in(parent)?,
+                None => Self::temp()?,
+            }
         } else if let Some(cache_dir) = cache_dir {
-            Ok(Self::from_path(cache_dir))
+            Self::from_path(cache_dir)
         } else {
             let cache_dir = rv_dirs::user_cache_dir(camino::Utf8Path::new("/"));
-            Ok(Self::from_path(cache_dir))
-        }
+            Self::from_path(cache_dir)
+        };
+        let jobs = jobs.unwrap_or_else(|| {
+            std::thread::available_parallelism().map_or(1, std::num::NonZero::get)
+        });
+        Ok(cache.with_jobs(jobs))
     }
 }
 
@@ -58,7 +82,12 @@ impl TryFrom<&CacheArgs> for Cache {
     type Error = io::Error;
 
     fn try_from(value: &CacheArgs) -> Result<Self, Self::Error> {
-        Cache::from_settings(value.no_cache, value.cache_dir.as_ref())
+        Cache::from_settings(
+            value.no_cache,
+            value.cache_dir.as_ref(),
+            value.cache_tmpdir.as_ref(),
+            value.cache_jobs,
+        )
     }
 }
 
@@ -77,7 +106,7 @@ mod tests {
 
     #[test]
     fn test_cache_from_settings_no_cache() {
-        let cache = Cache::from_settings(true, None).unwrap();
+        let cache = Cache::from_settings(true, None, None, None).unwrap();
         assert!(cache.is_temporary());
     }
 
@@ -86,14 +115,14 @@ mod tests {
         let temp_dir = tempdir().unwrap();
         let cache_path = camino::Utf8PathBuf::from(temp_dir.path().to_str().unwrap());
 
-        let cache = Cache::from_settings(false, Some(&cache_path)).unwrap();
+        let cache = Cache::from_settings(false, Some(&cache_path), None, None).unwrap();
         assert!(!cache.is_temporary());
         assert_eq!(cache.root(), cache_path);
     }
 
     #[test]
     fn test_cache_from_settings_default() {
-        let cache = Cache::from_settings(false, None).unwrap();
+        let cache = Cache::from_settings(false, None, None, None).unwrap();
         assert!(!cache.is_temporary());
         // Should use rv_dirs::user_cache_dir result
         assert!(!cache.root().as_str().is_empty());
@@ -107,6 +136,8 @@ mod tests {
         let args = CacheArgs {
             no_cache: false,
             cache_dir: Some(cache_path.clone()),
+            cache_tmpdir: None,
+            cache_jobs: None,
         };
 
         let cache: Cache = args.try_into().unwrap();
@@ -119,9 +150,41 @@ mod tests {
         let args = CacheArgs {
             no_cache: true,
             cache_dir: None,
+            cache_tmpdir: None,
+            cache_jobs: None,
         };
 
         let cache: Cache = args.try_into().unwrap();
         assert!(cache.is_temporary());
     }
+
+    #[test]
+    fn test_cache_args_try_from_cache_tmpdir() {
+        let temp_dir = tempdir().unwrap();
+        let parent = camino::Utf8PathBuf::from(temp_dir.path().to_str().unwrap());
+
+        let args = CacheArgs {
+            no_cache: true,
+            cache_dir: None,
+            cache_tmpdir: Some(parent.clone()),
+            cache_jobs: None,
+        };
+
+        let cache: Cache = args.try_into().unwrap();
+        assert!(cache.is_temporary());
+        assert!(cache.root().starts_with(&parent));
+    }
+
+    #[test]
+    fn test_cache_args_try_from_cache_jobs() {
+        let args = CacheArgs {
+            no_cache: true,
+            cache_dir: None,
+            cache_tmpdir: None,
+            cache_jobs: Some(4),
+        };
+
+        let cache: Cache = args.try_into().unwrap();
+        assert_eq!(cache.jobs(), 4);
+    }
 }
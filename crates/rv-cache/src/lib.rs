@@ -1,16 +1,24 @@
+use std::collections::BTreeMap;
 use std::fmt::{Display, Formatter};
 use std::io;
 use std::io::Write;
 use std::ops::Deref;
+use std::str::FromStr;
 use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 
 use camino::{Utf8Path, Utf8PathBuf};
-use tracing::debug;
+use sha2::{Digest, Sha256};
+use tracing::{debug, info, warn};
 
 #[cfg(feature = "clap")]
 pub use crate::cli::CacheArgs;
+pub use crate::migrate::{Migration, PreviousVersion};
+use crate::migrate::{migrate_bucket, migrate_bucket_dry_run};
+#[cfg(feature = "progress")]
+pub use crate::progress::ProgressReporter;
 use crate::removal::Remover;
-pub use crate::removal::{Removal, rm_rf};
+pub use crate::removal::{Removal, rm_rf, rm_rf_dry_run};
 
 // Re-export our custom caching utilities
 pub use crate::cache_key::{CacheKey, CacheKeyHasher, cache_digest};
@@ -19,6 +27,9 @@ pub use crate::timestamp::Timestamp;
 mod cache_key;
 #[cfg(feature = "clap")]
 mod cli;
+mod migrate;
+#[cfg(feature = "progress")]
+mod progress;
 mod removal;
 mod timestamp;
 
@@ -109,6 +120,19 @@ impl Deref for CacheShard {
     }
 }
 
+/// The name of the file in the cache root which [`Cache::lock`] locks.
+const LOCK_FILE: &str = ".lock";
+
+/// The prefix of the temporary files `tempfile` creates, which [`Cache::write_entry`] writes
+/// entries to before renaming them into place.
+const TEMP_FILE_PREFIX: &str = ".tmp";
+
+/// An advisory lock on a whole [`Cache`], held until it's dropped.
+#[derive(Debug)]
+pub struct CacheLock {
+    _file: std::fs::File,
+}
+
 /// The main cache abstraction.
 #[derive(Debug, Clone)]
 pub struct Cache {
@@ -119,6 +143,8 @@ pub struct Cache {
     /// Included to ensure that the temporary directory exists for the length of the operation, but
     /// is dropped at the end as appropriate.
     temp_dir: Option<Arc<tempfile::TempDir>>,
+    /// How many threads to remove entries on, when clearing or pruning the cache.
+    jobs: usize,
 }
 
 impl Cache {
@@ -127,17 +153,28 @@ impl Cache {
         Self {
             root: root.into(),
             temp_dir: None,
+            jobs: 1,
         }
     }
 
     /// Create a temporary cache directory.
     pub fn temp() -> Result<Self, io::Error> {
-        let temp_dir = tempfile::tempdir()?;
+        Self::from_temp_dir(tempfile::tempdir()?)
+    }
+
+    /// Create a temporary cache directory inside `parent`, rather than the system temp directory.
+    pub fn temp_in(parent: &Utf8Path) -> Result<Self, io::Error> {
+        fs_err::create_dir_all(parent)?;
+        Self::from_temp_dir(tempfile::tempdir_in(parent)?)
+    }
+
+    fn from_temp_dir(temp_dir: tempfile::TempDir) -> Result<Self, io::Error> {
         let root = Utf8PathBuf::try_from(temp_dir.path().to_path_buf())
             .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Invalid UTF-8 path"))?;
         Ok(Self {
             root,
             temp_dir: Some(Arc::new(temp_dir)),
+            jobs: 1,
         })
     }
 
@@ -156,6 +193,20 @@ impl Cache {
         CacheShard(self.bucket(cache_bucket).join(dir.as_ref()))
     }
 
+    /// Remove entries on up to `jobs` threads when clearing or pruning the cache.
+    #[must_use]
+    pub fn with_jobs(self, jobs: usize) -> Self {
+        Self {
+            jobs: jobs.max(1),
+            ..self
+        }
+    }
+
+    /// How many threads entries are removed on, when clearing or pruning the cache.
+    pub fn jobs(&self) -> usize {
+        self.jobs
+    }
+
     /// Compute an entry in the cache.
     pub fn entry(
         &self,
@@ -166,6 +217,137 @@ impl Cache {
         CacheEntry::new(self.bucket(cache_bucket).join(dir), file)
     }
 
+    /// Read the entry for `key` in the given bucket, or create it from the bytes returned by
+    /// `init` if it isn't cached yet.
+    ///
+    /// New entries are written with [`Cache::write_entry`].
+    pub async fn get_or_insert<F, Fut, E>(
+        &self,
+        cache_bucket: CacheBucket,
+        key: impl CacheKey,
+        init: F,
+    ) -> Result<Vec<u8>, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<Vec<u8>, E>>,
+        E: From<io::Error>,
+    {
+        let entry = CacheEntry::new(self.bucket(cache_bucket), cache_digest(key));
+
+        if let Some(bytes) = self.read_entry(&entry).await? {
+            return Ok(bytes);
+        }
+
+        let bytes = init().await?;
+        self.write_entry(&entry, &bytes).await?;
+        Ok(bytes)
+    }
+
+    /// Read a cache entry, or `None` if it doesn't exist.
+    pub async fn read_entry(&self, entry: &CacheEntry) -> Result<Option<Vec<u8>>, io::Error> {
+        match tokio::fs::read(entry.path()).await {
+            Ok(bytes) => {
+                debug!("Cache hit: {}", entry.path());
+                Ok(Some(bytes))
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                debug!("Cache miss: {}", entry.path());
+                Ok(None)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Read a cache entry, checking that its SHA-256 digest is `expected_sha256`.
+    ///
+    /// An entry which doesn't match is corrupt, so it's removed and reported as a miss, letting
+    /// the caller fetch it again.
+    pub async fn read_verified(
+        &self,
+        entry: &CacheEntry,
+        expected_sha256: &[u8],
+    ) -> Result<Option<Vec<u8>>, io::Error> {
+        let Some(bytes) = self.read_entry(entry).await? else {
+            return Ok(None);
+        };
+        if Sha256::digest(&bytes).as_slice() == expected_sha256 {
+            return Ok(Some(bytes));
+        }
+
+        warn!("Removing corrupt cache entry {}", entry.path());
+        match tokio::fs::remove_file(entry.path()).await {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(None),
+        }
+    }
+
+    /// Write a cache entry, creating its directory if needed.
+    ///
+    /// The entry is written to a temporary file and then renamed into place, so concurrent
+    /// readers never see a partially written entry.
+    pub async fn write_entry(&self, entry: &CacheEntry, bytes: &[u8]) -> Result<(), io::Error> {
+        tokio::fs::create_dir_all(entry.dir()).await?;
+        // `tempfile` only does blocking I/O, so keep it off the async runtime's threads.
+        let entry = entry.clone();
+        let bytes = bytes.to_vec();
+        tokio::task::spawn_blocking(move || {
+            let mut temp_file = tempfile::NamedTempFile::new_in(entry.dir())?;
+            temp_file.write_all(&bytes)?;
+            temp_file.persist(entry.path()).map_err(|err| err.error)?;
+            Ok::<_, io::Error>(())
+        })
+        .await
+        .map_err(io::Error::other)?
+    }
+
+    /// Lock the whole cache, waiting for any other process holding the lock to release it.
+    ///
+    /// The lock is advisory: it only keeps out other callers of [`Cache::lock`] and
+    /// [`Cache::try_lock`], so operations which shouldn't overlap, like pruning the cache and
+    /// installing from it, must take it first.
+    pub fn lock(&self) -> Result<CacheLock, io::Error> {
+        let file = self.open_lock_file()?;
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(std::fs::TryLockError::WouldBlock) => {
+                info!("Waiting for another rv process to release the cache lock");
+                file.lock()?;
+            }
+            Err(std::fs::TryLockError::Error(err)) => return Err(err),
+        }
+        Ok(CacheLock { _file: file })
+    }
+
+    /// Like [`Cache::lock`], but returns `None` straight away if someone else holds the lock.
+    pub fn try_lock(&self) -> Result<Option<CacheLock>, io::Error> {
+        let file = self.open_lock_file()?;
+        match file.try_lock() {
+            Ok(()) => Ok(Some(CacheLock { _file: file })),
+            Err(std::fs::TryLockError::WouldBlock) => Ok(None),
+            Err(std::fs::TryLockError::Error(err)) => Err(err),
+        }
+    }
+
+    /// Like [`Cache::lock`], but returns `None` instead of creating the cache if it doesn't exist
+    /// yet, for operations which have nothing to do without one.
+    pub fn lock_existing(&self) -> Result<Option<CacheLock>, io::Error> {
+        if !self.root.exists() {
+            return Ok(None);
+        }
+        self.lock().map(Some)
+    }
+
+    fn open_lock_file(&self) -> Result<std::fs::File, io::Error> {
+        fs_err::create_dir_all(&self.root)?;
+        let path = self.root.join(LOCK_FILE);
+        std::fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .map_err(|err| io::Error::new(err.kind(), format!("failed to open {path}: {err}")))
+    }
+
     /// Returns `true` if the [`Cache`] is temporary.
     pub fn is_temporary(&self) -> bool {
         self.temp_dir.is_some()
@@ -175,18 +357,21 @@ impl Cache {
     pub fn init(self) -> Result<Self, io::Error> {
         let root = &self.root;
 
-        // Create the cache directory, if it doesn't exist.
+        // Create the cache directory, if it doesn't exist. This is fine to race with other
+        // processes, since `create_dir_all` tolerates directories created concurrently.
         fs_err::create_dir_all(root)?;
 
-        // Add the .gitignore.
-        match fs_err::OpenOptions::new()
-            .write(true)
-            .create_new(true)
-            .open(root.join(".gitignore"))
-        {
-            Ok(mut file) => file.write_all(b"*")?,
-            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => (),
-            Err(err) => return Err(err),
+        // Add the .gitignore. It's written to a temporary file first, so that nobody else can see
+        // it half-written, and then moved into place unless another `init` got there first.
+        let gitignore = root.join(".gitignore");
+        if !gitignore.exists() {
+            let mut temp_file = tempfile::NamedTempFile::new_in(root)?;
+            temp_file.write_all(b"*")?;
+            match temp_file.persist_noclobber(&gitignore) {
+                Ok(_) => (),
+                Err(err) if err.error.kind() == io::ErrorKind::AlreadyExists => (),
+                Err(err) => return Err(err.error),
+            }
         }
 
         Ok(Self {
@@ -196,17 +381,191 @@ impl Cache {
     }
 
     /// Clear the cache, removing all entries.
+    ///
+    /// The lock file is kept, so that whoever holds [`Cache::lock`] while clearing keeps
+    /// everyone else out until they're done.
     pub fn clear(&self, reporter: Box<dyn CleanReporter>) -> Result<Removal, io::Error> {
-        Remover::new(reporter).rm_rf(&self.root)
+        if !self.root.exists() {
+            return Ok(Removal::default());
+        }
+        let mut entries = Vec::new();
+        for entry in fs_err::read_dir(&self.root)? {
+            let entry = entry?;
+            if entry.file_name() == LOCK_FILE {
+                continue;
+            }
+            let path = Utf8PathBuf::try_from(entry.path())
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Invalid UTF-8 path"))?;
+            entries.push(path);
+        }
+        let remover = Remover::new(reporter);
+        let removal = remover.rm_rf_all(&entries, self.jobs)?;
+        remover.complete();
+        Ok(removal)
+    }
+
+    /// Clear a single bucket, leaving the rest of the cache alone.
+    /// Clearing a bucket which hasn't been created yet removes nothing.
+    pub fn clear_bucket(
+        &self,
+        bucket: CacheBucket,
+        reporter: Box<dyn CleanReporter>,
+    ) -> Result<Removal, io::Error> {
+        let remover = Remover::new(reporter);
+        let removal = remover.rm_rf(&self.bucket(bucket))?;
+        remover.complete();
+        Ok(removal)
+    }
+
+    /// Every entry in a bucket, including those in its shards, sorted by path.
+    /// Buckets which haven't been created yet have no entries, and the temporary files
+    /// [`Cache::write_entry`] is still writing aren't entries yet.
+    pub fn entries(&self, bucket: CacheBucket) -> Result<Vec<CacheEntry>, io::Error> {
+        fn walk(dir: &Utf8Path, entries: &mut Vec<CacheEntry>) -> Result<(), io::Error> {
+            for entry in fs_err::read_dir(dir)? {
+                let entry = entry?;
+                let path = Utf8PathBuf::try_from(entry.path()).map_err(|_| {
+                    io::Error::new(io::ErrorKind::InvalidData, "Invalid UTF-8 path")
+                })?;
+                if entry.file_type()?.is_dir() {
+                    walk(&path, entries)?;
+                } else if !path
+                    .file_name()
+                    .is_some_and(|name| name.starts_with(TEMP_FILE_PREFIX))
+                {
+                    entries.push(CacheEntry::from_path(path));
+                }
+            }
+            Ok(())
+        }
+
+        let mut entries = Vec::new();
+        let bucket = self.bucket(bucket);
+        if bucket.is_dir() {
+            walk(&bucket, &mut entries)?;
+        }
+        entries.sort_by(|a, b| a.path().cmp(b.path()));
+        Ok(entries)
+    }
+
+    /// How many bytes each cache bucket takes up on disk.
+    /// Buckets which haven't been created yet take up 0 bytes.
+    pub fn size(&self) -> Result<BTreeMap<CacheBucket, u64>, io::Error> {
+        CacheBucket::iter()
+            .map(|bucket| Ok((bucket, rm_rf_dry_run(self.bucket(bucket))?.bytes)))
+            .collect()
+    }
+
+    /// Move the entries of older versions of each bucket into the current version, or remove
+    /// them if the current version can't use them. [`Cache::prune`] does this first, rather than
+    /// removing older versions outright.
+    pub fn migrate(&self, reporter: Box<dyn CleanReporter>) -> Result<Migration, io::Error> {
+        let remover = Remover::new(reporter);
+        let migration = self.migrate_buckets(&remover, CacheBucket::previous_versions)?;
+        remover.complete();
+        Ok(migration)
+    }
+
+    fn migrate_buckets(
+        &self,
+        remover: &Remover,
+        previous_versions: fn(CacheBucket) -> &'static [PreviousVersion],
+    ) -> Result<Migration, io::Error> {
+        let mut migration = Migration::default();
+        for bucket in CacheBucket::iter() {
+            migration += migrate_bucket(
+                &self.root,
+                bucket.to_str(),
+                previous_versions(bucket),
+                remover,
+            )?;
+        }
+        Ok(migration)
     }
 
     /// Run the garbage collector on the cache, removing any unused entries.
-    pub fn prune(&self) -> Result<Removal, io::Error> {
+    ///
+    /// Older versions of each bucket are migrated with [`Cache::migrate`] first, so the entries
+    /// the current versions can use are kept.
+    pub fn prune(&self, reporter: Box<dyn CleanReporter>) -> Result<Removal, io::Error> {
+        self.prune_migrating(reporter, CacheBucket::previous_versions)
+    }
+
+    fn prune_migrating(
+        &self,
+        reporter: Box<dyn CleanReporter>,
+        previous_versions: fn(CacheBucket) -> &'static [PreviousVersion],
+    ) -> Result<Removal, io::Error> {
+        let remover = Remover::new(reporter);
+        let migration = self.migrate_buckets(&remover, previous_versions)?;
+        let unused = self.unused_entries()?;
+        for path in &unused {
+            debug!("Removing dangling cache entry: {}", path);
+        }
+        let summary = migration.removal + remover.rm_rf_all(&unused, self.jobs)?;
+        remover.complete();
+        Ok(summary)
+    }
+
+    /// Report what [`Cache::prune`] would remove, without removing anything.
+    pub fn prune_dry_run(&self) -> Result<Removal, io::Error> {
+        self.prune_dry_run_migrating(CacheBucket::previous_versions)
+    }
+
+    fn prune_dry_run_migrating(
+        &self,
+        previous_versions: fn(CacheBucket) -> &'static [PreviousVersion],
+    ) -> Result<Removal, io::Error> {
         let mut summary = Removal::default();
+        let mut migrated = Vec::new();
+        for bucket in CacheBucket::iter() {
+            let previous = previous_versions(bucket);
+            summary += migrate_bucket_dry_run(&self.root, bucket.to_str(), previous)?.removal;
+            migrated.extend(previous.iter().map(|version| self.root.join(version.dir)));
+        }
+        for path in self.unused_entries()? {
+            // Older versions of buckets are migrated rather than pruned, and counted above.
+            if migrated.contains(&path) {
+                continue;
+            }
+            debug!("Would remove dangling cache entry: {}", path);
+            summary += rm_rf_dry_run(path)?;
+        }
+        Ok(summary)
+    }
+
+    /// Remove entries from every cache bucket that haven't been modified within `max_age`,
+    /// along with any directories that are left empty.
+    pub fn prune_older_than(
+        &self,
+        max_age: Duration,
+        reporter: Box<dyn CleanReporter>,
+    ) -> Result<Removal, io::Error> {
+        let cutoff = Timestamp::from(
+            SystemTime::now()
+                .checked_sub(max_age)
+                .unwrap_or(SystemTime::UNIX_EPOCH),
+        );
+        let remover = Remover::new(reporter);
+        let mut summary = Removal::default();
+        for bucket in CacheBucket::iter() {
+            let bucket = self.bucket(bucket);
+            if bucket.exists() {
+                summary += prune_stale_entries(&remover, &bucket, cutoff)?;
+            }
+        }
+        remover.complete();
+        Ok(summary)
+    }
+
+    /// Top-level entries in the cache that aren't used any more, i.e. the ones that
+    /// [`Cache::prune`] removes.
+    fn unused_entries(&self) -> Result<Vec<Utf8PathBuf>, io::Error> {
+        let mut unused = Vec::new();
 
         if !&self.root.exists() {
             debug!("No cache found at: {}", &self.root);
-            return Ok(summary);
+            return Ok(unused);
         }
 
         // Remove any top-level directories that are unused. These typically represent
@@ -215,34 +574,52 @@ impl Cache {
             let entry = entry?;
             let metadata = entry.metadata()?;
 
-            if entry.file_name() == ".gitignore" {
+            if entry.file_name() == ".gitignore" || entry.file_name() == LOCK_FILE {
                 continue;
             }
 
-            if metadata.is_dir() {
-                // If the directory is not a cache bucket, remove it.
-                let entry_name = entry.file_name();
-                if CacheBucket::iter().all(|bucket| entry_name != bucket.to_str()) {
-                    let path = Utf8PathBuf::try_from(entry.path()).map_err(|_| {
-                        io::Error::new(io::ErrorKind::InvalidData, "Invalid UTF-8 path")
-                    })?;
-                    debug!("Removing dangling cache bucket: {}", path);
-                    summary += rm_rf(path)?;
-                }
-            } else {
-                // If the file is not a marker file, remove it.
-                let path = Utf8PathBuf::try_from(entry.path()).map_err(|_| {
-                    io::Error::new(io::ErrorKind::InvalidData, "Invalid UTF-8 path")
-                })?;
-                debug!("Removing dangling cache file: {}", path);
-                summary += rm_rf(path)?;
+            // If the directory is not a cache bucket, or the file is not a marker file, remove it.
+            let entry_name = entry.file_name();
+            if metadata.is_dir() && CacheBucket::iter().any(|bucket| entry_name == bucket.to_str())
+            {
+                continue;
             }
+            let path = Utf8PathBuf::try_from(entry.path())
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Invalid UTF-8 path"))?;
+            unused.push(path);
         }
 
-        Ok(summary)
+        Ok(unused)
     }
 }
 
+/// Remove the files below `dir` last modified before `cutoff`, then any directories they
+/// leave empty. `dir` itself is kept.
+fn prune_stale_entries(
+    remover: &Remover,
+    dir: &Utf8Path,
+    cutoff: Timestamp,
+) -> Result<Removal, io::Error> {
+    let mut removal = Removal::default();
+    for entry in fs_err::read_dir(dir)? {
+        let entry = entry?;
+        let path = Utf8PathBuf::try_from(entry.path())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Invalid UTF-8 path"))?;
+
+        if entry.file_type()?.is_dir() {
+            removal += prune_stale_entries(remover, &path, cutoff)?;
+            if fs_err::read_dir(&path)?.next().is_none() {
+                debug!("Removing empty cache directory: {}", path);
+                removal += remover.rm_rf(&path)?;
+            }
+        } else if Timestamp::from(entry.metadata()?.modified()?) < cutoff {
+            debug!("Removing stale cache entry: {}", path);
+            removal += remover.rm_rf(&path)?;
+        }
+    }
+    Ok(removal)
+}
+
 pub trait CleanReporter: Send + Sync {
     /// Called after one file or directory is removed.
     fn on_clean(&self);
@@ -253,24 +630,46 @@ pub trait CleanReporter: Send + Sync {
 
 /// The different kinds of data in the cache are stored in different buckets, which in our case
 /// are subdirectories of the cache root.
-#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
 pub enum CacheBucket {
     /// Ruby interpreters and metadata.
     ///
     /// Cache structure: `ruby-v0/<digest(path)>.json`
     Ruby,
+    /// Gems downloaded from gem servers.
+    ///
+    /// Cache structure: `gem-v0/<digest(key)>`
+    Gem,
 }
 
 impl CacheBucket {
     fn to_str(self) -> &'static str {
         match self {
             Self::Ruby => "ruby-v0",
+            Self::Gem => "gem-v0",
+        }
+    }
+
+    /// Older versions of this bucket, newest first, for [`Cache::migrate`] to handle.
+    /// When bumping a bucket's version, add the version it replaces here.
+    fn previous_versions(self) -> &'static [PreviousVersion] {
+        match self {
+            Self::Ruby | Self::Gem => &[],
+        }
+    }
+
+    /// The name of the bucket without its version, e.g. `ruby`.
+    fn short_name(self) -> &'static str {
+        match self {
+            Self::Ruby => "ruby",
+            Self::Gem => "gem",
         }
     }
 
     /// Return an iterator over all cache buckets.
     pub fn iter() -> impl Iterator<Item = Self> {
-        [Self::Ruby].iter().copied()
+        [Self::Ruby, Self::Gem].iter().copied()
     }
 }
 
@@ -280,12 +679,49 @@ impl Display for CacheBucket {
     }
 }
 
+impl FromStr for CacheBucket {
+    type Err = UnknownCacheBucket;
+
+    /// Accepts either the short name of a bucket (`gem`) or its directory name (`gem-v0`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::iter()
+            .find(|bucket| s == bucket.to_str() || s == bucket.short_name())
+            .ok_or_else(|| UnknownCacheBucket(s.to_owned()))
+    }
+}
+
+impl TryFrom<&str> for CacheBucket {
+    type Error = UnknownCacheBucket;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+/// A string which doesn't name any [`CacheBucket`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownCacheBucket(String);
+
+impl Display for UnknownCacheBucket {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let expected: Vec<_> = CacheBucket::iter().map(CacheBucket::short_name).collect();
+        write!(
+            f,
+            "unknown cache bucket {:?}, expected one of: {}",
+            self.0,
+            expected.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for UnknownCacheBucket {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     /// Test reporter that tracks cleanup operations
-    #[derive(Default, Debug)]
+    #[derive(Default, Debug, Clone)]
     struct TestReporter {
         cleaned: std::sync::Arc<std::sync::atomic::AtomicUsize>,
         completed: std::sync::Arc<std::sync::atomic::AtomicBool>,
@@ -322,11 +758,31 @@ mod tests {
         assert_eq!(CacheBucket::Ruby.to_string(), "ruby-v0");
     }
 
+    #[test]
+    fn test_cache_bucket_from_str() {
+        assert_eq!("ruby".parse(), Ok(CacheBucket::Ruby));
+        assert_eq!("gem".parse(), Ok(CacheBucket::Gem));
+        assert_eq!("ruby-v0".parse(), Ok(CacheBucket::Ruby));
+        assert_eq!(CacheBucket::try_from("gem-v0"), Ok(CacheBucket::Gem));
+        for bucket in CacheBucket::iter() {
+            assert_eq!(bucket.to_string().parse(), Ok(bucket));
+        }
+
+        let err = "gems".parse::<CacheBucket>().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            r#"unknown cache bucket "gems", expected one of: ruby, gem"#
+        );
+        assert!(CacheBucket::try_from("gem-v1").is_err());
+        assert!(CacheBucket::try_from("").is_err());
+    }
+
     #[test]
     fn test_cache_bucket_iteration() {
         let buckets: Vec<_> = CacheBucket::iter().collect();
-        assert_eq!(buckets.len(), 1);
+        assert_eq!(buckets.len(), 2);
         assert!(buckets.contains(&CacheBucket::Ruby));
+        assert!(buckets.contains(&CacheBucket::Gem));
     }
 
     #[test]
@@ -376,6 +832,24 @@ mod tests {
         assert!(!cache.root().as_str().is_empty());
     }
 
+    #[test]
+    fn test_cache_temp_in() {
+        use tempfile::tempdir;
+
+        let temp_dir = tempdir().unwrap();
+        let parent = camino::Utf8PathBuf::from(temp_dir.path().to_str().unwrap()).join("scratch");
+
+        let cache = Cache::temp_in(&parent).unwrap();
+        assert!(cache.is_temporary());
+        assert!(cache.root().starts_with(&parent));
+        assert!(cache.root().is_dir());
+
+        let root = cache.root().to_owned();
+        drop(cache);
+        assert!(!root.exists());
+        assert!(parent.is_dir());
+    }
+
     #[test]
     fn test_cache_bucket_paths() {
         let cache = Cache::from_path("/test/cache");
@@ -457,10 +931,426 @@ mod tests {
         fs_err::write(test_dir.join("file.txt"), "content").unwrap();
 
         let reporter = TestReporter::new();
-        let removal = cache.clear(Box::new(reporter)).unwrap();
+        let removal = cache.clear(Box::new(reporter.clone())).unwrap();
 
         assert!(!removal.is_empty());
         assert!(removal.bytes > 0);
+        assert!(reporter.is_completed());
+    }
+
+    #[test]
+    fn test_cache_concurrent_init() {
+        use tempfile::tempdir;
+
+        let temp_dir = tempdir().unwrap();
+        let cache_path = camino::Utf8PathBuf::from(temp_dir.path().to_str().unwrap())
+            .join("nested")
+            .join("cache");
+
+        let roots: Vec<_> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..8)
+                .map(|_| scope.spawn(|| Cache::from_path(&cache_path).init()))
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap().unwrap().root().to_owned())
+                .collect()
+        });
+
+        let canonical = cache_path.canonicalize_utf8().unwrap();
+        assert!(roots.iter().all(|root| *root == canonical));
+        assert_eq!(
+            fs_err::read_to_string(cache_path.join(".gitignore")).unwrap(),
+            "*"
+        );
+        // No temporary files are left behind by the `init`s which lost the race.
+        assert_eq!(fs_err::read_dir(&cache_path).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_cache_migrate_current_versions() {
+        use tempfile::tempdir;
+
+        let temp_dir = tempdir().unwrap();
+        let cache_path = camino::Utf8PathBuf::from(temp_dir.path().to_str().unwrap());
+        let cache = Cache::from_path(&cache_path).init().unwrap();
+        let gem_bucket = cache.bucket(CacheBucket::Gem);
+        fs_err::create_dir_all(&gem_bucket).unwrap();
+        fs_err::write(gem_bucket.join("rack.gem"), "rack").unwrap();
+
+        let migration = cache.migrate(Box::new(TestReporter::new())).unwrap();
+        assert!(migration.is_empty());
+        assert!(gem_bucket.join("rack.gem").exists());
+    }
+
+    #[test]
+    fn test_cache_clear_with_jobs() {
+        use tempfile::tempdir;
+
+        let temp_dir = tempdir().unwrap();
+        let cache_path = camino::Utf8PathBuf::from(temp_dir.path().to_str().unwrap()).join("cache");
+        let cache = Cache::from_path(&cache_path).init().unwrap().with_jobs(4);
+        for shard in 0..16 {
+            let shard = cache.shard(CacheBucket::Gem, format!("shard-{shard}"));
+            fs_err::create_dir_all(shard.as_std_path()).unwrap();
+            fs_err::write(shard.join("rack.gem"), "0123456789").unwrap();
+        }
+
+        let reporter = TestReporter::new();
+        let cleaned = reporter.cleaned.clone();
+        let removal = cache.clear(Box::new(reporter)).unwrap();
+
+        // 16 shards and the bucket, with one gem in each shard and the .gitignore.
+        assert_eq!(removal, Removal::new(17, 16 * 10 + 1));
+        assert_eq!(cleaned.load(std::sync::atomic::Ordering::SeqCst), 17 + 17);
+        assert_eq!(fs_err::read_dir(&cache_path).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_cache_entries() {
+        use tempfile::tempdir;
+
+        let temp_dir = tempdir().unwrap();
+        let cache_path = camino::Utf8PathBuf::from(temp_dir.path().to_str().unwrap());
+        let cache = Cache::from_path(&cache_path).init().unwrap();
+        assert!(cache.entries(CacheBucket::Gem).unwrap().is_empty());
+
+        let shard = cache.shard(CacheBucket::Gem, "rack");
+        fs_err::create_dir_all(shard.as_std_path()).unwrap();
+        fs_err::write(shard.join("rack-3.1.0.gem"), "rack").unwrap();
+        fs_err::write(cache.bucket(CacheBucket::Gem).join("abc"), "rake").unwrap();
+        fs_err::write(shard.join(".tmpAbC123"), "half a gem").unwrap();
+
+        let entries: Vec<_> = cache
+            .entries(CacheBucket::Gem)
+            .unwrap()
+            .into_iter()
+            .map(CacheEntry::into_path_buf)
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                cache.bucket(CacheBucket::Gem).join("abc"),
+                shard.join("rack-3.1.0.gem"),
+            ]
+        );
+        assert!(cache.entries(CacheBucket::Ruby).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_cache_try_lock() {
+        use tempfile::tempdir;
+
+        let temp_dir = tempdir().unwrap();
+        let cache_path = camino::Utf8PathBuf::from(temp_dir.path().to_str().unwrap());
+        let cache = Cache::from_path(cache_path.join("cache"));
+
+        let lock = cache.lock().unwrap();
+        assert!(cache.try_lock().unwrap().is_none());
+        drop(lock);
+
+        let lock = cache.try_lock().unwrap();
+        assert!(lock.is_some());
+        assert!(cache.try_lock().unwrap().is_none());
+        drop(lock);
+        assert!(cache.try_lock().unwrap().is_some());
+
+        // The lock file isn't mistaken for a dangling entry.
+        assert!(cache.prune_dry_run().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_cache_lock_existing() {
+        use tempfile::tempdir;
+
+        let temp_dir = tempdir().unwrap();
+        let cache_path = camino::Utf8PathBuf::from(temp_dir.path().to_str().unwrap());
+        let cache = Cache::from_path(cache_path.join("cache"));
+
+        // Locking a cache which doesn't exist doesn't create it.
+        assert!(cache.lock_existing().unwrap().is_none());
+        assert!(!cache.root().exists());
+
+        let cache = cache.init().unwrap();
+        let lock = cache.lock_existing().unwrap();
+        assert!(lock.is_some());
+        assert!(cache.try_lock().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_cache_clear_keeps_lock() {
+        use tempfile::tempdir;
+
+        let temp_dir = tempdir().unwrap();
+        let cache_path = camino::Utf8PathBuf::from(temp_dir.path().to_str().unwrap());
+        let cache = Cache::from_path(cache_path.join("cache")).init().unwrap();
+        fs_err::write(cache.root().join("test.txt"), "test content").unwrap();
+
+        // Nobody else can take the lock while the cache is cleared, or afterwards.
+        let lock = cache.lock().unwrap();
+        cache.clear(Box::new(TestReporter::new())).unwrap();
+        assert!(!cache.root().join("test.txt").exists());
+        assert!(cache.root().join(LOCK_FILE).exists());
+        assert!(cache.try_lock().unwrap().is_none());
+        drop(lock);
+        assert!(cache.try_lock().unwrap().is_some());
+    }
+
+    #[test]
+    fn test_cache_clear_bucket() {
+        use tempfile::tempdir;
+
+        let temp_dir = tempdir().unwrap();
+        let cache_path = camino::Utf8PathBuf::from(temp_dir.path().to_str().unwrap());
+        let cache = Cache::from_path(&cache_path).init().unwrap();
+
+        // Nothing to clear yet.
+        let removal = cache
+            .clear_bucket(CacheBucket::Gem, Box::new(TestReporter::new()))
+            .unwrap();
+        assert!(removal.is_empty());
+
+        for bucket in CacheBucket::iter() {
+            fs_err::create_dir_all(cache.bucket(bucket)).unwrap();
+        }
+        let ruby_file = cache.bucket(CacheBucket::Ruby).join("ruby.json");
+        fs_err::write(&ruby_file, "{}").unwrap();
+        fs_err::write(
+            cache.bucket(CacheBucket::Gem).join("rack.gem"),
+            "0123456789",
+        )
+        .unwrap();
+
+        let reporter = TestReporter::new();
+        let completed = reporter.completed.clone();
+        let removal = cache
+            .clear_bucket(CacheBucket::Gem, Box::new(reporter))
+            .unwrap();
+        assert_eq!(removal, Removal::new(1, 10));
+        assert!(completed.load(std::sync::atomic::Ordering::SeqCst));
+        assert!(!cache.bucket(CacheBucket::Gem).exists());
+        assert!(ruby_file.exists());
+    }
+
+    #[test]
+    fn test_cache_size() {
+        use tempfile::tempdir;
+
+        let temp_dir = tempdir().unwrap();
+        let cache_path = camino::Utf8PathBuf::from(temp_dir.path().to_str().unwrap());
+        let cache = Cache::from_path(&cache_path).init().unwrap();
+
+        // Nothing in the cache yet.
+        let size = cache.size().unwrap();
+        assert_eq!(size[&CacheBucket::Ruby], 0);
+        assert_eq!(size[&CacheBucket::Gem], 0);
+
+        let ruby_shard = cache.shard(CacheBucket::Ruby, "interpreters");
+        fs_err::create_dir_all(ruby_shard.as_std_path()).unwrap();
+        fs_err::write(ruby_shard.join("a.json"), "{}").unwrap();
+        fs_err::write(ruby_shard.join("b.json"), "[1, 2]").unwrap();
+        fs_err::create_dir_all(cache.bucket(CacheBucket::Gem)).unwrap();
+        fs_err::write(
+            cache.bucket(CacheBucket::Gem).join("rack.gem"),
+            "0123456789",
+        )
+        .unwrap();
+        // Not a bucket, so not counted.
+        fs_err::write(cache_path.join("random.txt"), "content").unwrap();
+
+        let size = cache.size().unwrap();
+        assert_eq!(size.len(), 2);
+        assert_eq!(size[&CacheBucket::Ruby], 8);
+        assert_eq!(size[&CacheBucket::Gem], 10);
+    }
+
+    #[tokio::test]
+    async fn test_cache_get_or_insert() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use tempfile::tempdir;
+
+        let temp_dir = tempdir().unwrap();
+        let cache_path = camino::Utf8PathBuf::from(temp_dir.path().to_str().unwrap());
+        let cache = Cache::from_path(&cache_path).init().unwrap();
+        let calls = &AtomicUsize::new(0);
+        let init = move || async move {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Ok::<_, io::Error>(b"gem contents".to_vec())
+        };
+
+        // A miss runs init and stores its result.
+        let bytes = cache
+            .get_or_insert(CacheBucket::Gem, "rack-3.1.0", init)
+            .await
+            .unwrap();
+        assert_eq!(bytes, b"gem contents");
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        let entry = cache
+            .bucket(CacheBucket::Gem)
+            .join(cache_digest("rack-3.1.0"));
+        assert_eq!(fs_err::read(&entry).unwrap(), b"gem contents");
+
+        // A hit reads the stored bytes without running init.
+        let bytes = cache
+            .get_or_insert(CacheBucket::Gem, "rack-3.1.0", init)
+            .await
+            .unwrap();
+        assert_eq!(bytes, b"gem contents");
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // Errors from init are passed through, and nothing is stored.
+        let err = cache
+            .get_or_insert(CacheBucket::Gem, "rack-3.2.0", || async {
+                Err::<Vec<u8>, _>(io::Error::other("download failed"))
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "download failed");
+        assert!(
+            !cache
+                .bucket(CacheBucket::Gem)
+                .join(cache_digest("rack-3.2.0"))
+                .exists()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cache_read_write_entry() {
+        use tempfile::tempdir;
+
+        let temp_dir = tempdir().unwrap();
+        let cache_path = camino::Utf8PathBuf::from(temp_dir.path().to_str().unwrap());
+        let cache = Cache::from_path(&cache_path).init().unwrap();
+        let entry = cache.entry(CacheBucket::Gem, "rack", "rack-3.1.0.gem");
+
+        // The entry's directory doesn't even exist yet.
+        assert_eq!(cache.read_entry(&entry).await.unwrap(), None);
+
+        cache.write_entry(&entry, b"gem contents").await.unwrap();
+        assert_eq!(
+            cache.read_entry(&entry).await.unwrap().as_deref(),
+            Some(&b"gem contents"[..])
+        );
+
+        // Overwriting replaces the whole entry.
+        cache.write_entry(&entry, b"new").await.unwrap();
+        assert_eq!(
+            cache.read_entry(&entry).await.unwrap().as_deref(),
+            Some(&b"new"[..])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cache_read_entry_deleted_after_check() {
+        use tempfile::tempdir;
+
+        let temp_dir = tempdir().unwrap();
+        let cache_path = camino::Utf8PathBuf::from(temp_dir.path().to_str().unwrap());
+        let cache = Cache::from_path(&cache_path).init().unwrap();
+        let entry = cache.entry(CacheBucket::Gem, "rack", "rack-3.1.0.gem");
+        cache.write_entry(&entry, b"gem contents").await.unwrap();
+
+        // Someone else removes the entry after a caller checked it exists, but before it's read,
+        // e.g. by running `rv cache clean`. That's just a miss.
+        assert!(entry.path().exists());
+        fs_err::remove_file(entry.path()).unwrap();
+        assert_eq!(cache.read_entry(&entry).await.unwrap(), None);
+
+        // Even when the whole cache goes away.
+        cache.write_entry(&entry, b"gem contents").await.unwrap();
+        fs_err::remove_dir_all(&cache_path).unwrap();
+        assert_eq!(cache.read_entry(&entry).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_cache_read_verified() {
+        use tempfile::tempdir;
+
+        let temp_dir = tempdir().unwrap();
+        let cache_path = camino::Utf8PathBuf::from(temp_dir.path().to_str().unwrap());
+        let cache = Cache::from_path(&cache_path).init().unwrap();
+        let entry = cache.entry(CacheBucket::Gem, "rack", "rack-3.1.0.gem");
+        let expected = Sha256::digest(b"gem contents");
+
+        assert_eq!(cache.read_verified(&entry, &expected).await.unwrap(), None);
+
+        cache.write_entry(&entry, b"gem contents").await.unwrap();
+        assert_eq!(
+            cache.read_verified(&entry, &expected).await.unwrap(),
+            Some(b"gem contents".to_vec())
+        );
+
+        // A corrupt entry is a miss, and is removed so that it's fetched again.
+        cache.write_entry(&entry, b"gem c0ntents").await.unwrap();
+        assert_eq!(cache.read_verified(&entry, &expected).await.unwrap(), None);
+        assert!(!entry.path().exists());
+    }
+
+    #[test]
+    fn test_cache_write_entry_is_atomic() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use tempfile::tempdir;
+
+        let temp_dir = tempdir().unwrap();
+        let cache_path = camino::Utf8PathBuf::from(temp_dir.path().to_str().unwrap());
+        let cache = Cache::from_path(&cache_path).init().unwrap();
+        let entry = cache.entry(CacheBucket::Gem, "rack", "rack-3.1.0.gem");
+        let versions: Vec<Vec<u8>> = (0..20u8).map(|i| vec![i; 1024 * 1024]).collect();
+        let done = AtomicBool::new(false);
+
+        std::thread::scope(|scope| {
+            // Keep reading while the entry is rewritten, checking that every read sees one of
+            // the versions in full.
+            let reader = scope.spawn(|| {
+                while !done.load(Ordering::SeqCst) {
+                    if let Ok(bytes) = std::fs::read(entry.path()) {
+                        assert!(versions.contains(&bytes), "saw a partial write");
+                    }
+                }
+            });
+
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .build()
+                .unwrap();
+            for version in &versions {
+                runtime
+                    .block_on(cache.write_entry(&entry, version))
+                    .unwrap();
+            }
+            done.store(true, Ordering::SeqCst);
+            reader.join().unwrap();
+        });
+    }
+
+    #[tokio::test]
+    async fn test_temp_cache_dedupes_within_a_run() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        // With caching disabled, every part of a run shares clones of one temporary cache.
+        let cache = Cache::temp().unwrap().init().unwrap();
+        let other = cache.clone();
+        let fetches = &AtomicUsize::new(0);
+        let fetch = move || async move {
+            fetches.fetch_add(1, Ordering::SeqCst);
+            Ok::<_, io::Error>(b"gem contents".to_vec())
+        };
+
+        // The same gem, requested twice, is only fetched once.
+        for cache in [&cache, &other] {
+            let bytes = cache
+                .get_or_insert(CacheBucket::Gem, "rack-3.1.0", fetch)
+                .await
+                .unwrap();
+            assert_eq!(bytes, b"gem contents");
+        }
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+
+        // Once the run is over, everything is thrown away.
+        let root = cache.root().to_owned();
+        drop(cache);
+        assert!(root.exists());
+        drop(other);
+        assert!(!root.exists());
     }
 
     #[test]
@@ -485,7 +1375,12 @@ mod tests {
         // Create a random file (should be removed)
         fs_err::write(cache_path.join("random.txt"), "content").unwrap();
 
-        let removal = cache.prune().unwrap();
+        let reporter = TestReporter::new();
+        let removal = cache.prune(Box::new(reporter.clone())).unwrap();
+
+        // The invalid bucket, the file inside it, and the random file.
+        assert_eq!(reporter.cleaned_count(), 3);
+        assert!(reporter.is_completed());
 
         // Valid bucket should remain
         assert!(valid_bucket.exists());
@@ -502,6 +1397,128 @@ mod tests {
         assert!(!removal.is_empty());
     }
 
+    #[test]
+    fn test_cache_prune_dry_run() {
+        use tempfile::tempdir;
+
+        let temp_dir = tempdir().unwrap();
+        let cache_path = temp_dir.path().join("cache");
+        let cache_path_utf8 = camino::Utf8PathBuf::from(cache_path.to_str().unwrap());
+        let cache = Cache::from_path(&cache_path_utf8).init().unwrap();
+
+        let valid_bucket = cache_path.join("ruby-v0");
+        fs_err::create_dir(&valid_bucket).unwrap();
+        fs_err::write(valid_bucket.join("test.json"), "{}").unwrap();
+        let invalid_bucket = cache_path.join("ruby-v-0");
+        fs_err::create_dir_all(invalid_bucket.join("nested")).unwrap();
+        fs_err::write(invalid_bucket.join("old.json"), "{}").unwrap();
+        fs_err::write(invalid_bucket.join("nested").join("older.json"), "{ }").unwrap();
+        fs_err::write(cache_path.join("random.txt"), "content").unwrap();
+
+        let dry_run = cache.prune_dry_run().unwrap();
+
+        // Nothing should have been removed.
+        assert!(invalid_bucket.exists());
+        assert!(cache_path.join("random.txt").exists());
+        assert_eq!(dry_run, Removal::new(2, 2 + 3 + 7));
+
+        // A real prune should remove exactly what the dry run reported.
+        let removal = cache.prune(Box::new(TestReporter::new())).unwrap();
+        assert_eq!(removal, dry_run);
+        assert!(!invalid_bucket.exists());
+        assert!(valid_bucket.exists());
+    }
+
+    #[test]
+    fn test_cache_prune_keeps_migrated_entries() {
+        use tempfile::tempdir;
+
+        fn previous_versions(bucket: CacheBucket) -> &'static [PreviousVersion] {
+            match bucket {
+                CacheBucket::Ruby => &[PreviousVersion {
+                    dir: "ruby-v-1",
+                    compatible: false,
+                }],
+                CacheBucket::Gem => &[PreviousVersion {
+                    dir: "gem-v-1",
+                    compatible: true,
+                }],
+            }
+        }
+
+        let temp_dir = tempdir().unwrap();
+        let cache_path = camino::Utf8PathBuf::from(temp_dir.path().to_str().unwrap());
+        let cache = Cache::from_path(&cache_path).init().unwrap();
+        let old_gems = cache_path.join("gem-v-1");
+        fs_err::create_dir_all(&old_gems).unwrap();
+        fs_err::write(old_gems.join("rack.gem"), "rack").unwrap();
+        let old_rubies = cache_path.join("ruby-v-1");
+        fs_err::create_dir_all(&old_rubies).unwrap();
+        fs_err::write(old_rubies.join("ruby.json"), "{}").unwrap();
+        fs_err::write(cache_path.join("random.txt"), "content").unwrap();
+
+        let dry_run = cache.prune_dry_run_migrating(previous_versions).unwrap();
+        assert!(old_gems.exists());
+        assert!(old_rubies.exists());
+
+        let removal = cache
+            .prune_migrating(Box::new(TestReporter::new()), previous_versions)
+            .unwrap();
+
+        // The compatible bucket's entries are moved, rather than removed with the old version.
+        assert_eq!(removal, Removal::new(2, 2 + 7));
+        assert_eq!(removal, dry_run);
+        assert_eq!(
+            fs_err::read_to_string(cache.bucket(CacheBucket::Gem).join("rack.gem")).unwrap(),
+            "rack"
+        );
+        assert!(!old_gems.exists());
+        assert!(!old_rubies.exists());
+        assert!(!cache_path.join("random.txt").exists());
+    }
+
+    #[test]
+    fn test_cache_prune_older_than() {
+        use tempfile::tempdir;
+
+        let temp_dir = tempdir().unwrap();
+        let cache_path = camino::Utf8PathBuf::from(temp_dir.path().to_str().unwrap());
+        let cache = Cache::from_path(&cache_path).init().unwrap();
+
+        let ruby_shard = cache.shard(CacheBucket::Ruby, "interpreters");
+        fs_err::create_dir_all(ruby_shard.as_std_path()).unwrap();
+        fs_err::write(ruby_shard.join("fresh.json"), "{}").unwrap();
+        let gem_bucket = cache.bucket(CacheBucket::Gem);
+        fs_err::create_dir_all(gem_bucket.join("old")).unwrap();
+        let stale = gem_bucket.join("old").join("rack.gem");
+        fs_err::write(&stale, "0123456789").unwrap();
+
+        let two_days_ago = SystemTime::now() - Duration::from_secs(2 * 24 * 60 * 60);
+        std::fs::File::options()
+            .write(true)
+            .open(&stale)
+            .unwrap()
+            .set_modified(two_days_ago)
+            .unwrap();
+
+        let reporter = TestReporter::new();
+        let removal = cache
+            .prune_older_than(
+                Duration::from_secs(24 * 60 * 60),
+                Box::new(reporter.clone()),
+            )
+            .unwrap();
+
+        // The stale gem and the directory it leaves empty.
+        assert_eq!(removal, Removal::new(1, 10));
+        assert_eq!(reporter.cleaned_count(), 2);
+        assert!(reporter.is_completed());
+        assert!(!stale.exists());
+        assert!(!gem_bucket.join("old").exists());
+        assert!(gem_bucket.exists());
+        assert!(ruby_shard.join("fresh.json").exists());
+    }
+
     #[test]
     fn test_removal_display() {
         let removal = super::removal::Removal::new(0, 0);
@@ -558,7 +1575,7 @@ mod tests {
 
         assert!(!file_path.exists());
         assert_eq!(removal.dirs, 0);
-        assert!(removal.bytes > 0); // Should have removed some bytes
+        assert_eq!(removal.bytes, "test content".len() as u64);
     }
 
     #[test]
@@ -580,8 +1597,8 @@ mod tests {
         let removal = super::removal::rm_rf(&utf8_path).unwrap();
 
         assert!(!dir_path.exists());
-        assert!(removal.dirs > 0); // Should have removed directories
-        assert!(removal.bytes > 0); // Should have removed some bytes
+        assert_eq!(removal.dirs, 2);
+        assert_eq!(removal.bytes, 16);
     }
 
     #[test]
@@ -0,0 +1,218 @@
+use std::collections::HashSet;
+use std::io;
+use std::ops::AddAssign;
+
+use camino::{Utf8Path, Utf8PathBuf};
+use tracing::debug;
+
+use crate::removal::{Removal, Remover, rm_rf_dry_run};
+
+/// An older version of a cache bucket, e.g. `gem-v0` once gems are stored in `gem-v1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PreviousVersion {
+    /// The name of the bucket's directory in that version.
+    pub dir: &'static str,
+    /// Whether its entries can be used as they are by the current version.
+    /// If they can't, they're removed instead.
+    pub compatible: bool,
+}
+
+/// A summary of what [`Cache::migrate`](crate::Cache::migrate) did.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Migration {
+    /// The number of entries moved into the current version of their bucket.
+    pub moved: u64,
+    /// The older versions of buckets which were removed, along with any entries that couldn't
+    /// be moved.
+    pub removal: Removal,
+}
+
+impl Migration {
+    /// Returns `true` if there was nothing to migrate.
+    pub fn is_empty(&self) -> bool {
+        self.moved == 0 && self.removal.is_empty()
+    }
+}
+
+impl AddAssign for Migration {
+    fn add_assign(&mut self, other: Self) {
+        self.moved += other.moved;
+        self.removal += other.removal;
+    }
+}
+
+/// Move the entries of the `previous` versions of a bucket into its `current` directory, then
+/// remove the old directories. Entries which are already in the current version are kept, so
+/// `previous` should be ordered newest first.
+pub(crate) fn migrate_bucket(
+    root: &Utf8Path,
+    current: &str,
+    previous: &[PreviousVersion],
+    remover: &Remover,
+) -> Result<Migration, io::Error> {
+    let mut migration = Migration::default();
+    let current = root.join(current);
+
+    for version in previous {
+        let old = root.join(version.dir);
+        if !old.is_dir() {
+            continue;
+        }
+
+        if version.compatible {
+            debug!("Migrating cache bucket {} to {}", old, current);
+            fs_err::create_dir_all(&current)?;
+            for entry in fs_err::read_dir(&old)? {
+                let entry_path = Utf8PathBuf::try_from(entry?.path()).map_err(|_| {
+                    io::Error::new(io::ErrorKind::InvalidData, "Invalid UTF-8 path")
+                })?;
+                let Some(name) = entry_path.file_name() else {
+                    continue;
+                };
+                let target = current.join(name);
+                if fs_err::symlink_metadata(&target).is_ok() {
+                    continue;
+                }
+                fs_err::rename(&entry_path, &target)?;
+                migration.moved += 1;
+            }
+        } else {
+            debug!("Removing incompatible cache bucket {}", old);
+        }
+
+        migration.removal += remover.rm_rf(&old)?;
+    }
+
+    Ok(migration)
+}
+
+/// Report what [`migrate_bucket`] would do, without moving or removing anything.
+pub(crate) fn migrate_bucket_dry_run(
+    root: &Utf8Path,
+    current: &str,
+    previous: &[PreviousVersion],
+) -> Result<Migration, io::Error> {
+    let mut migration = Migration::default();
+    let current = root.join(current);
+
+    // The names in the current version, including the ones that would be moved into it.
+    let mut names = HashSet::new();
+    if current.is_dir() {
+        for entry in fs_err::read_dir(&current)? {
+            names.insert(entry?.file_name());
+        }
+    }
+
+    for version in previous {
+        let old = root.join(version.dir);
+        if !old.is_dir() {
+            continue;
+        }
+
+        if !version.compatible {
+            migration.removal += rm_rf_dry_run(&old)?;
+            continue;
+        }
+
+        for entry in fs_err::read_dir(&old)? {
+            let entry = entry?;
+            if names.insert(entry.file_name()) {
+                migration.moved += 1;
+                continue;
+            }
+            let entry_path = Utf8PathBuf::try_from(entry.path())
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Invalid UTF-8 path"))?;
+            migration.removal += rm_rf_dry_run(&entry_path)?;
+        }
+        migration.removal += Removal::new(1, 0);
+    }
+
+    Ok(migration)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CleanReporter;
+    use assert_fs::TempDir;
+    use std::fs;
+
+    struct NoopReporter;
+
+    impl CleanReporter for NoopReporter {
+        fn on_clean(&self) {}
+        fn on_complete(&self) {}
+    }
+
+    fn setup() -> (TempDir, Utf8PathBuf) {
+        let temp_dir = TempDir::new().unwrap();
+        let root = Utf8PathBuf::from_path_buf(temp_dir.path().to_owned()).unwrap();
+        fs::create_dir_all(root.join("gem-v0")).unwrap();
+        fs::write(root.join("gem-v0").join("rack.gem"), "rack").unwrap();
+        fs::write(root.join("gem-v0").join("rake.gem"), "old rake").unwrap();
+        fs::create_dir_all(root.join("gem-v1")).unwrap();
+        fs::write(root.join("gem-v1").join("rake.gem"), "rake").unwrap();
+        (temp_dir, root)
+    }
+
+    #[test]
+    fn test_migrate_compatible_version() {
+        let (_temp_dir, root) = setup();
+        let previous = [PreviousVersion {
+            dir: "gem-v0",
+            compatible: true,
+        }];
+
+        let dry_run = migrate_bucket_dry_run(&root, "gem-v1", &previous).unwrap();
+        let remover = Remover::new(Box::new(NoopReporter));
+        let migration = migrate_bucket(&root, "gem-v1", &previous, &remover).unwrap();
+        assert_eq!(migration, dry_run);
+
+        // The new version's entry wins, and the old one is removed along with its bucket.
+        assert_eq!(
+            migration,
+            Migration {
+                moved: 1,
+                removal: Removal::new(1, "old rake".len() as u64),
+            }
+        );
+        assert!(!root.join("gem-v0").exists());
+        assert_eq!(
+            fs::read_to_string(root.join("gem-v1").join("rack.gem")).unwrap(),
+            "rack"
+        );
+        assert_eq!(
+            fs::read_to_string(root.join("gem-v1").join("rake.gem")).unwrap(),
+            "rake"
+        );
+
+        // Migrating again is a no-op.
+        let migration = migrate_bucket(&root, "gem-v1", &previous, &remover).unwrap();
+        assert!(migration.is_empty());
+    }
+
+    #[test]
+    fn test_migrate_incompatible_version() {
+        let (_temp_dir, root) = setup();
+        let previous = [PreviousVersion {
+            dir: "gem-v0",
+            compatible: false,
+        }];
+
+        let dry_run = migrate_bucket_dry_run(&root, "gem-v1", &previous).unwrap();
+        let remover = Remover::new(Box::new(NoopReporter));
+        let migration = migrate_bucket(&root, "gem-v1", &previous, &remover).unwrap();
+        assert_eq!(migration, dry_run);
+
+        assert_eq!(
+            migration,
+            Migration {
+                moved: 0,
+                removal: Removal::new(1, ("rack".len() + "old rake".len()) as u64),
+            }
+        );
+        assert!(!root.join("gem-v0").exists());
+        assert!(!root.join("gem-v1").join("rack.gem").exists());
+        assert!(root.join("gem-v1").join("rake.gem").exists());
+    }
+}
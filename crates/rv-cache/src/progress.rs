@@ -0,0 +1,64 @@
+use indicatif::{ProgressBar, ProgressStyle};
+
+use crate::CleanReporter;
+
+/// A [`CleanReporter`] which shows how many cache entries have been removed so far, on a spinner
+/// drawn to stderr. Nothing is drawn if `RV_DISABLE_INDICATIF` is set.
+pub struct ProgressReporter {
+    bar: ProgressBar,
+}
+
+impl ProgressReporter {
+    pub fn new() -> Self {
+        let bar = if std::env::var_os("RV_DISABLE_INDICATIF").is_some() {
+            ProgressBar::hidden()
+        } else {
+            ProgressBar::new_spinner()
+        };
+        bar.set_style(
+            ProgressStyle::with_template("{spinner} Removed {pos} cache entries")
+                .expect("progress template is valid"),
+        );
+        Self { bar }
+    }
+
+    /// How many files and directories have been removed so far.
+    pub fn cleaned(&self) -> u64 {
+        self.bar.position()
+    }
+}
+
+impl Default for ProgressReporter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CleanReporter for ProgressReporter {
+    fn on_clean(&self) {
+        self.bar.inc(1);
+    }
+
+    fn on_complete(&self) {
+        self.bar.finish_and_clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_reporter_counts_cleans() {
+        let reporter = ProgressReporter {
+            bar: ProgressBar::hidden(),
+        };
+        for _ in 0..5 {
+            reporter.on_clean();
+        }
+        assert_eq!(reporter.cleaned(), 5);
+
+        reporter.on_complete();
+        assert_eq!(reporter.cleaned(), 5);
+    }
+}
@@ -1,6 +1,7 @@
 use std::fmt::Display;
 use std::io;
 use std::ops::{Add, AddAssign};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use camino::{Utf8Path, Utf8PathBuf};
 use tracing::debug;
@@ -18,26 +19,67 @@ impl Remover {
 
     pub fn rm_rf(&self, path: &Utf8Path) -> Result<Removal, io::Error> {
         debug!("Removing cache entry: {}", path);
+        let path = &extended_length_path(path)?;
 
-        if !path.exists() {
+        let Some(metadata) = symlink_metadata_if_exists(path)? else {
             return Ok(Removal::default());
-        }
+        };
 
-        let removal = if path.is_dir() {
+        let removal = if metadata.is_dir() {
             let removal = self.rm_rf_dir(path)?;
-            fs_err::remove_dir(path)?;
+            remove_dir(path, &metadata)?;
             self.reporter.on_clean();
             removal + Removal::new(1, 0)
         } else {
-            let metadata = fs_err::metadata(path)?;
-            fs_err::remove_file(path)?;
+            // Symlinks are removed, rather than followed.
+            remove_file(path, &metadata)?;
             self.reporter.on_clean();
-            Removal::new(0, metadata.len())
+            Removal::new(0, freed_bytes(&metadata))
         };
 
         Ok(removal)
     }
 
+    /// Remove each of `paths` with [`Remover::rm_rf`], on up to `jobs` threads.
+    pub fn rm_rf_all(&self, paths: &[Utf8PathBuf], jobs: usize) -> Result<Removal, io::Error> {
+        let jobs = jobs.clamp(1, paths.len().max(1));
+        if jobs == 1 {
+            return paths.iter().try_fold(Removal::default(), |removal, path| {
+                Ok(removal + self.rm_rf(path)?)
+            });
+        }
+
+        // Each worker takes the next path until there are none left, so one huge shard doesn't
+        // hold up the paths queued behind it.
+        let next = AtomicUsize::new(0);
+        std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..jobs)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut removal = Removal::default();
+                        while let Some(path) = paths.get(next.fetch_add(1, Ordering::Relaxed)) {
+                            removal += self.rm_rf(path)?;
+                        }
+                        Ok::<_, io::Error>(removal)
+                    })
+                })
+                .collect();
+            workers
+                .into_iter()
+                .try_fold(Removal::default(), |removal, worker| {
+                    let worker_removal = worker
+                        .join()
+                        .unwrap_or_else(|panic| std::panic::resume_unwind(panic))?;
+                    Ok(removal + worker_removal)
+                })
+        })
+    }
+
+    /// Let the reporter know that all removals are done.
+    pub fn complete(&self) {
+        self.reporter.on_complete();
+    }
+
     fn rm_rf_dir(&self, path: &Utf8Path) -> Result<Removal, io::Error> {
         let mut removal = Removal::default();
 
@@ -46,16 +88,17 @@ impl Remover {
             let entry_path = Utf8PathBuf::try_from(entry.path())
                 .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Invalid UTF-8 path"))?;
 
-            if entry_path.is_dir() {
+            // Unlike `Path::is_dir`, this doesn't follow symlinks.
+            let metadata = entry.metadata()?;
+            if entry.file_type()?.is_dir() {
                 removal += self.rm_rf_dir(&entry_path)?;
-                fs_err::remove_dir(&entry_path)?;
+                remove_dir(&entry_path, &metadata)?;
                 self.reporter.on_clean();
                 removal += Removal::new(1, 0);
             } else {
-                let metadata = entry.metadata()?;
-                fs_err::remove_file(&entry_path)?;
+                remove_file(&entry_path, &metadata)?;
                 self.reporter.on_clean();
-                removal += Removal::new(0, metadata.len());
+                removal += Removal::new(0, freed_bytes(&metadata));
             }
         }
 
@@ -64,36 +107,177 @@ impl Remover {
 }
 
 /// Remove a file or directory recursively.
+/// Symlinks are removed, rather than followed.
 pub fn rm_rf(path: impl AsRef<Utf8Path>) -> Result<Removal, io::Error> {
-    let path = path.as_ref();
+    rm_rf_inner(&extended_length_path(path.as_ref())?)
+}
 
-    if !path.exists() {
+fn rm_rf_inner(path: &Utf8Path) -> Result<Removal, io::Error> {
+    let Some(metadata) = symlink_metadata_if_exists(path)? else {
         return Ok(Removal::default());
+    };
+
+    if metadata.is_dir() {
+        let mut removal = Removal::default();
+
+        for entry in fs_err::read_dir(path)? {
+            let entry = entry?;
+            let entry_path = Utf8PathBuf::try_from(entry.path())
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Invalid UTF-8 path"))?;
+            removal += rm_rf_inner(&entry_path)?;
+        }
+
+        remove_dir(path, &metadata)?;
+        removal += Removal::new(1, 0);
+
+        Ok(removal)
+    } else {
+        remove_file(path, &metadata)?;
+        Ok(Removal::new(0, freed_bytes(&metadata)))
     }
+}
 
-    if path.is_dir() {
+/// Report what [`rm_rf`] would remove, without removing anything.
+pub fn rm_rf_dry_run(path: impl AsRef<Utf8Path>) -> Result<Removal, io::Error> {
+    rm_rf_dry_run_inner(path.as_ref(), &mut HardLinks::default())
+}
+
+fn rm_rf_dry_run_inner(path: &Utf8Path, hard_links: &mut HardLinks) -> Result<Removal, io::Error> {
+    let Some(metadata) = symlink_metadata_if_exists(path)? else {
+        return Ok(Removal::default());
+    };
+
+    if metadata.is_dir() {
         let mut removal = Removal::default();
 
         for entry in fs_err::read_dir(path)? {
             let entry = entry?;
             let entry_path = Utf8PathBuf::try_from(entry.path())
                 .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Invalid UTF-8 path"))?;
-            removal += rm_rf(&entry_path)?;
+            removal += rm_rf_dry_run_inner(&entry_path, hard_links)?;
         }
 
-        fs_err::remove_dir(path)?;
         removal += Removal::new(1, 0);
 
         Ok(removal)
     } else {
-        let metadata = fs_err::metadata(path)?;
-        fs_err::remove_file(path)?;
-        Ok(Removal::new(0, metadata.len()))
+        Ok(Removal::new(0, hard_links.freed_bytes(&metadata)))
+    }
+}
+
+/// How many bytes removing this file frees up.
+///
+/// Only file contents are counted, not the size of directories themselves, which varies between
+/// filesystems. If other hard links still point at the file's data, removing it frees nothing.
+/// That data is counted once the last link is removed instead.
+fn freed_bytes(metadata: &std::fs::Metadata) -> u64 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        if metadata.is_file() && metadata.nlink() > 1 {
+            return 0;
+        }
+    }
+    metadata.len()
+}
+
+/// Tracks hard links seen during a dry run, where link counts don't go down as files are
+/// "removed", so that [`rm_rf_dry_run`] reports the same bytes as [`rm_rf`] would.
+#[derive(Default)]
+struct HardLinks {
+    /// How many links to each (device, inode) have been seen.
+    #[cfg(unix)]
+    seen: std::collections::HashMap<(u64, u64), u64>,
+}
+
+impl HardLinks {
+    fn freed_bytes(&mut self, metadata: &std::fs::Metadata) -> u64 {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            if metadata.is_file() && metadata.nlink() > 1 {
+                let seen = self
+                    .seen
+                    .entry((metadata.dev(), metadata.ino()))
+                    .or_default();
+                *seen += 1;
+                // Only the last link frees the data.
+                return if *seen == metadata.nlink() {
+                    metadata.len()
+                } else {
+                    0
+                };
+            }
+        }
+        metadata.len()
+    }
+}
+
+/// Remove a file or symlink, clearing its read-only attribute first on Windows.
+fn remove_file(path: &Utf8Path, metadata: &std::fs::Metadata) -> Result<(), io::Error> {
+    clear_readonly(path, metadata)?;
+    fs_err::remove_file(path)
+}
+
+/// Remove an empty directory, clearing its read-only attribute first on Windows.
+fn remove_dir(path: &Utf8Path, metadata: &std::fs::Metadata) -> Result<(), io::Error> {
+    clear_readonly(path, metadata)?;
+    fs_err::remove_dir(path)
+}
+
+/// Windows refuses to delete anything with the read-only attribute set, which is common in
+/// extracted gems. Elsewhere, removal depends on the parent directory's permissions instead.
+#[cfg(windows)]
+fn clear_readonly(path: &Utf8Path, metadata: &std::fs::Metadata) -> Result<(), io::Error> {
+    // Setting permissions follows symlinks, so leave those alone.
+    if metadata.is_symlink() || !metadata.permissions().readonly() {
+        return Ok(());
+    }
+    let mut permissions = metadata.permissions();
+    #[allow(clippy::permissions_set_readonly_false)]
+    permissions.set_readonly(false);
+    fs_err::set_permissions(path, permissions)
+}
+
+#[cfg(not(windows))]
+fn clear_readonly(_path: &Utf8Path, _metadata: &std::fs::Metadata) -> Result<(), io::Error> {
+    Ok(())
+}
+
+/// Deeply nested gem directories can be longer than Windows' `MAX_PATH`, so remove them through
+/// an extended-length path (`\\?\C:\...`), which isn't subject to that limit.
+#[cfg(windows)]
+fn extended_length_path(path: &Utf8Path) -> Result<Utf8PathBuf, io::Error> {
+    if path.as_str().starts_with(r"\\?\") {
+        return Ok(path.to_owned());
+    }
+    // Extended-length paths must be absolute, with no `/` separators or `..` components,
+    // and `absolute` takes care of all of that.
+    let absolute = Utf8PathBuf::try_from(std::path::absolute(path)?)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Invalid UTF-8 path"))?;
+    Ok(match absolute.as_str().strip_prefix(r"\\") {
+        Some(unc) => Utf8PathBuf::from(format!(r"\\?\UNC\{unc}")),
+        None => Utf8PathBuf::from(format!(r"\\?\{absolute}")),
+    })
+}
+
+#[cfg(not(windows))]
+fn extended_length_path(path: &Utf8Path) -> Result<Utf8PathBuf, io::Error> {
+    Ok(path.to_owned())
+}
+
+/// Metadata of the file, directory or symlink at this path (without following symlinks),
+/// or `None` if there's nothing there.
+fn symlink_metadata_if_exists(path: &Utf8Path) -> Result<Option<std::fs::Metadata>, io::Error> {
+    match fs_err::symlink_metadata(path) {
+        Ok(metadata) => Ok(Some(metadata)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err),
     }
 }
 
 /// A summary of the files and directories removed from the cache.
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct Removal {
     /// The number of directories removed.
     pub dirs: u64,
@@ -241,6 +425,176 @@ mod tests {
         assert!(!dir_path.exists());
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_remover_does_not_follow_symlinks() {
+        let temp_dir = TempDir::new().unwrap();
+        let outside_dir = temp_dir.path().join("outside");
+        let outside_file = outside_dir.join("important.txt");
+        fs::create_dir_all(&outside_dir).unwrap();
+        fs::write(&outside_file, "important").unwrap();
+
+        let dir_path = Utf8PathBuf::from_path_buf(temp_dir.path().join("cache")).unwrap();
+        fs::create_dir_all(&dir_path).unwrap();
+        std::os::unix::fs::symlink(&outside_file, dir_path.join("file_link")).unwrap();
+        std::os::unix::fs::symlink(&outside_dir, dir_path.join("dir_link")).unwrap();
+
+        let (reporter, counter) = TestReporter::new();
+        let remover = Remover::new(Box::new(reporter));
+        let result = remover.rm_rf(&dir_path).unwrap();
+
+        assert!(!dir_path.exists());
+        assert!(outside_file.exists());
+        assert_eq!(result.dirs, 1);
+        // 2 links + the cache dir
+        assert_eq!(counter.load(std::sync::atomic::Ordering::SeqCst), 3);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_rm_rf_does_not_follow_symlinks() {
+        let temp_dir = TempDir::new().unwrap();
+        let outside_dir = temp_dir.path().join("outside");
+        let outside_file = outside_dir.join("important.txt");
+        fs::create_dir_all(&outside_dir).unwrap();
+        fs::write(&outside_file, "important").unwrap();
+
+        let dir_path = Utf8PathBuf::from_path_buf(temp_dir.path().join("cache")).unwrap();
+        fs::create_dir_all(&dir_path).unwrap();
+        let file_link = dir_path.join("file_link");
+        let dir_link = dir_path.join("dir_link");
+        let dangling_link = dir_path.join("dangling_link");
+        std::os::unix::fs::symlink(&outside_file, &file_link).unwrap();
+        std::os::unix::fs::symlink(&outside_dir, &dir_link).unwrap();
+        std::os::unix::fs::symlink(temp_dir.path().join("missing"), &dangling_link).unwrap();
+
+        // Removing a link directly removes only the link.
+        rm_rf(&dir_link).unwrap();
+        assert!(fs::symlink_metadata(&dir_link).is_err());
+        assert!(outside_file.exists());
+
+        let result = rm_rf(&dir_path).unwrap();
+        assert!(!dir_path.exists());
+        assert!(fs::symlink_metadata(&dangling_link).is_err());
+        assert!(outside_file.exists());
+        assert_eq!(result.dirs, 1);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_rm_rf_removes_read_only_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir_path = Utf8PathBuf::from_path_buf(temp_dir.path().join("cache")).unwrap();
+        let make_read_only_gem = || {
+            let file_path = dir_path.join("gems").join("rake.rb");
+            fs::create_dir_all(file_path.parent().unwrap()).unwrap();
+            fs::write(&file_path, "12345").unwrap();
+            let mut permissions = fs::metadata(&file_path).unwrap().permissions();
+            permissions.set_readonly(true);
+            fs::set_permissions(&file_path, permissions).unwrap();
+        };
+
+        make_read_only_gem();
+        let result = rm_rf(&dir_path).unwrap();
+        assert!(!dir_path.exists());
+        assert_eq!(result, Removal::new(2, 5));
+
+        make_read_only_gem();
+        let (reporter, _counter) = TestReporter::new();
+        let result = Remover::new(Box::new(reporter)).rm_rf(&dir_path).unwrap();
+        assert!(!dir_path.exists());
+        assert_eq!(result, Removal::new(2, 5));
+    }
+
+    #[test]
+    fn test_remover_parallel_matches_sequential() {
+        let temp_dir = TempDir::new().unwrap();
+        let make_cache = |name: &str| {
+            let dir_path = Utf8PathBuf::from_path_buf(temp_dir.path().join(name)).unwrap();
+            for shard in 0..32 {
+                let shard_path = dir_path.join(format!("shard-{shard}"));
+                fs::create_dir_all(shard_path.join("nested")).unwrap();
+                fs::write(shard_path.join("a.gem"), "x".repeat(shard)).unwrap();
+                fs::write(shard_path.join("nested").join("b.gem"), "12345").unwrap();
+            }
+            fs::write(dir_path.join(".gitignore"), "*").unwrap();
+            dir_path
+        };
+
+        let entries = |dir_path: &Utf8Path| -> Vec<Utf8PathBuf> {
+            fs::read_dir(dir_path)
+                .unwrap()
+                .map(|entry| Utf8PathBuf::from_path_buf(entry.unwrap().path()).unwrap())
+                .collect()
+        };
+
+        let sequential_path = make_cache("sequential");
+        let (reporter, sequential_count) = TestReporter::new();
+        let sequential = Remover::new(Box::new(reporter))
+            .rm_rf_all(&entries(&sequential_path), 1)
+            .unwrap();
+
+        let parallel_path = make_cache("parallel");
+        let (reporter, parallel_count) = TestReporter::new();
+        let parallel = Remover::new(Box::new(reporter))
+            .rm_rf_all(&entries(&parallel_path), 4)
+            .unwrap();
+
+        assert_eq!(fs::read_dir(&parallel_path).unwrap().count(), 0);
+        assert_eq!(parallel, sequential);
+        assert_eq!(
+            parallel_count.load(std::sync::atomic::Ordering::SeqCst),
+            sequential_count.load(std::sync::atomic::Ordering::SeqCst)
+        );
+    }
+
+    #[test]
+    fn test_remover_exact_bytes() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir_path = Utf8PathBuf::from_path_buf(temp_dir.path().join("test_dir")).unwrap();
+        let subdir_path = dir_path.join("subdir");
+        fs::create_dir_all(&subdir_path).unwrap();
+        fs::write(dir_path.join("a.txt"), "12345").unwrap();
+        fs::write(subdir_path.join("b.txt"), "1234567890").unwrap();
+        fs::write(subdir_path.join("empty.txt"), "").unwrap();
+
+        let dry_run = rm_rf_dry_run(&dir_path).unwrap();
+        let (reporter, _counter) = TestReporter::new();
+        let result = Remover::new(Box::new(reporter)).rm_rf(&dir_path).unwrap();
+
+        // Directories themselves don't count towards the bytes.
+        assert_eq!(result, Removal::new(2, 15));
+        assert_eq!(dry_run, result);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_rm_rf_hard_links() {
+        let temp_dir = TempDir::new().unwrap();
+        let outside_file = temp_dir.path().join("outside.txt");
+        fs::write(&outside_file, "shared with outside").unwrap();
+
+        let dir_path = Utf8PathBuf::from_path_buf(temp_dir.path().join("cache")).unwrap();
+        fs::create_dir_all(&dir_path).unwrap();
+        // Two links to the same data inside the directory.
+        fs::write(dir_path.join("inside.txt"), "0123456789").unwrap();
+        fs::hard_link(
+            dir_path.join("inside.txt"),
+            dir_path.join("inside_link.txt"),
+        )
+        .unwrap();
+        // And one link to data which lives on outside the directory.
+        fs::hard_link(&outside_file, dir_path.join("outside_link.txt")).unwrap();
+
+        let dry_run = rm_rf_dry_run(&dir_path).unwrap();
+        let result = rm_rf(&dir_path).unwrap();
+
+        // The shared data is counted once, and data kept alive by the outside link isn't.
+        assert_eq!(result, Removal::new(1, 10));
+        assert_eq!(dry_run, result);
+        assert!(outside_file.exists());
+    }
+
     #[test]
     fn test_removal_arithmetic() {
         let removal1 = Removal::new(2, 100);
@@ -1,6 +1,28 @@
 //! Most of the types in this module borrow a string from their input,
 //! so they have a lifetime 'i, which is short for 'input.
 
+/// A platform gems can be built for, like `ruby`, `java` or `arm64-darwin-23`.
+/// These are listed in the `PLATFORMS` section, and suffixed to native specs' versions.
+pub use rv_gem_types::Platform;
+
+/// (De)serializes platforms by name, as they're written in the lockfile.
+#[cfg(feature = "serde")]
+mod platform_names {
+    use super::Platform;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(platforms: &[Platform], s: S) -> Result<S::Ok, S::Error> {
+        s.collect_seq(platforms.iter().map(ToString::to_string))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<Platform>, D::Error> {
+        Vec::<String>::deserialize(d)?
+            .iter()
+            .map(|name| Platform::new(name).map_err(serde::de::Error::custom))
+            .collect()
+    }
+}
+
 #[derive(Debug, Clone, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GemfileDotLock<'i> {
@@ -17,16 +39,20 @@ pub struct GemfileDotLock<'i> {
     pub path: Vec<PathSection<'i>>,
 
     /// Lists every triple that Bundler has resolved and included in this lockfile.
-    #[serde(default, skip_serializing_if = "Vec::is_empty")]
-    pub platforms: Vec<&'i str>,
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        with = "platform_names"
+    )]
+    pub platforms: Vec<Platform>,
 
     /// Lists every gem that this lockfile has been resolved to include
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub dependencies: Vec<GemRange<'i>>,
 
     /// Which version of Ruby this lockfile was built with.
-    #[serde(default, skip_serializing_if = "Option::is_none")]
-    pub ruby_version: Option<&'i str>,
+    #[serde(borrow, default, skip_serializing_if = "Option::is_none")]
+    pub ruby_version: Option<RubyVersion<'i>>,
 
     /// Which version of Bundler this lockfile was built with.
     pub bundled_with: Option<&'i str>,
@@ -36,6 +62,111 @@ pub struct GemfileDotLock<'i> {
     pub checksums: Option<Vec<Checksum<'i>>>,
 }
 
+impl<'i> GemfileDotLock<'i> {
+    /// Find the checksum recorded in the `CHECKSUMS` section for this gem version, if any.
+    pub fn checksum_for(&self, gem_version: &GemVersion<'_>) -> Option<&Checksum<'i>> {
+        self.checksums
+            .as_ref()?
+            .iter()
+            .find(|checksum| &checksum.gem_version == gem_version)
+    }
+
+    /// Find the spec for the given gem in the RubyGems sources, along with the
+    /// section it came from. If several platform variants of the gem are locked,
+    /// returns the first one.
+    pub fn find_spec(&self, name: &str) -> Option<(&GemSection<'i>, &Spec<'i>)> {
+        self.gem.iter().find_map(|section| {
+            section
+                .specs
+                .iter()
+                .find(|spec| spec.gem_version.name == name)
+                .map(|spec| (section, spec))
+        })
+    }
+
+    /// Every locked spec, from all Git, RubyGems and path sources.
+    pub fn specs(&self) -> impl Iterator<Item = &Spec<'i>> {
+        let git = self.git.iter().flat_map(|section| section.specs.iter());
+        let gem = self.gem.iter().flat_map(|section| section.specs.iter());
+        let path = self.path.iter().flat_map(|section| section.specs.iter());
+        git.chain(gem).chain(path)
+    }
+
+    /// Every spec from RubyGems sources that should be installed on the given platform.
+    /// Native builds which match the platform are preferred. If a gem version has no
+    /// native build for this platform, its pure-Ruby variant is used instead.
+    pub fn gem_specs_for_platform(&self, platform: &Platform) -> Vec<&Spec<'i>> {
+        let specs: Vec<&Spec<'i>> = self
+            .gem
+            .iter()
+            .flat_map(|section| section.specs.iter())
+            .collect();
+        specs
+            .iter()
+            .copied()
+            .filter(|spec| {
+                if spec.matches_platform(platform) {
+                    return true;
+                }
+                // Fall back to the `ruby` variant, but only if no native build matches.
+                spec.gem_version.platform().is_none()
+                    && !specs.iter().any(|other| {
+                        other.gem_version.name == spec.gem_version.name
+                            && other.gem_version.number() == spec.gem_version.number()
+                            && other.matches_platform(platform)
+                    })
+            })
+            .collect()
+    }
+}
+
+/// The Ruby that a lockfile was built with, from its `RUBY VERSION` section.
+/// E.g. `ruby 3.3.4p94`, or `ruby 3.1.4p0 (jruby 9.4.0.0)` for other engines.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct RubyVersion<'i> {
+    /// Ruby implementation, e.g. `ruby` or `jruby`.
+    pub engine: &'i str,
+    /// Version of the engine, if the engine isn't `ruby`.
+    pub engine_version: Option<&'i str>,
+    /// Version of the Ruby language, e.g. `3.3.4`.
+    pub version: &'i str,
+    /// Patchlevel of the Ruby language version, e.g. `94`.
+    pub patchlevel: Option<&'i str>,
+}
+
+impl std::fmt::Display for RubyVersion<'_> {
+    /// Formats the version the same way Bundler writes it in lockfiles.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ruby {}", self.version)?;
+        if let Some(patchlevel) = self.patchlevel {
+            write!(f, "p{patchlevel}")?;
+        }
+        if let Some(engine_version) = self.engine_version
+            && self.engine != "ruby"
+        {
+            write!(f, " ({} {engine_version})", self.engine)?;
+        }
+        Ok(())
+    }
+}
+
+/// Serialized as the string Bundler would write, e.g. `ruby 3.3.4p94`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for RubyVersion<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de: 'i, 'i> serde::Deserialize<'de> for RubyVersion<'i> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = <&'de str as serde::Deserialize>::deserialize(deserializer)?;
+        crate::parser::parse_ruby_version_str(s)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid Ruby version: {s}")))
+    }
+}
+
 /// Git source that gems could come from.
 #[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -44,8 +175,14 @@ pub struct GitSection<'i> {
     pub remote: &'i str,
     /// Commit used from the Git repo.
     pub revision: &'i str,
+    /// Ref requested from the Git repo, if the Gemfile specified one.
+    #[serde(rename = "ref", default, skip_serializing_if = "Option::is_none")]
+    pub git_ref: Option<&'i str>,
     /// Branch used from the Git repo.
     pub branch: Option<&'i str>,
+    /// Tag requested from the Git repo, if the Gemfile specified one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<&'i str>,
     /// All gems which came from this source in particular.
     pub specs: Vec<Spec<'i>>,
 }
@@ -54,8 +191,8 @@ pub struct GitSection<'i> {
 #[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GemSection<'i> {
-    /// Location of the RubyGems server.
-    pub remote: &'i str,
+    /// Locations of the RubyGems servers, in the order they should be tried.
+    pub remotes: Vec<&'i str>,
     /// All gems which came from this source in particular.
     pub specs: Vec<Spec<'i>>,
 }
@@ -80,6 +217,58 @@ pub struct GemVersion<'i> {
     pub version: &'i str,
 }
 
+impl std::fmt::Display for GemVersion<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.name, self.version)
+    }
+}
+
+impl<'i> GemVersion<'i> {
+    /// The version number, without any platform suffix.
+    /// E.g. `1.18.10` for `nokogiri (1.18.10-arm64-darwin)`.
+    pub fn number(&self) -> &'i str {
+        self.split_platform().0
+    }
+
+    /// The platform this gem version was built for, if it isn't a pure-Ruby gem.
+    /// E.g. `arm64-darwin` for `nokogiri (1.18.10-arm64-darwin)`.
+    pub fn platform(&self) -> Option<&'i str> {
+        self.split_platform().1
+    }
+
+    /// Compare the version numbers of two gem versions the way RubyGems does, ignoring their
+    /// names and platforms. See [`compare_versions`].
+    pub fn cmp_version(&self, other: &GemVersion<'_>) -> std::cmp::Ordering {
+        compare_versions(self.number(), other.number())
+    }
+
+    /// Is this version newer than `version`? E.g. `rack (3.1.0)` is newer than `3.0.9`.
+    pub fn is_newer_than(&self, version: &str) -> bool {
+        compare_versions(self.number(), version).is_gt()
+    }
+
+    fn split_platform(&self) -> (&'i str, Option<&'i str>) {
+        match self.version.split_once('-') {
+            Some((number, platform)) => (number, Some(platform)),
+            None => (self.version, None),
+        }
+    }
+}
+
+/// Compare two version numbers using RubyGems' precedence rules, so `1.0.0.pre` comes before
+/// `1.0.0`, `1.10` comes after `1.9`, and `1.0` equals `1.0.0`.
+/// Versions RubyGems would reject are compared as plain strings, after all valid ones.
+pub fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
+    use std::cmp::Ordering;
+
+    match (rv_version::Version::new(a), rv_version::Version::new(b)) {
+        (Ok(a), Ok(b)) => a.cmp(&b),
+        (Ok(_), Err(_)) => Ordering::Less,
+        (Err(_), Ok(_)) => Ordering::Greater,
+        (Err(_), Err(_)) => a.cmp(b),
+    }
+}
+
 /// A range of possible versions of a certain gem.
 #[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -91,6 +280,26 @@ pub struct GemRange<'i> {
     pub nonstandard: bool,
 }
 
+impl std::fmt::Display for GemRange<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name)?;
+        if let Some(semver) = &self.semver {
+            f.write_str(" (")?;
+            for (n, constraint) in semver.iter().enumerate() {
+                if n > 0 {
+                    f.write_str(", ")?;
+                }
+                write!(f, "{constraint}")?;
+            }
+            f.write_str(")")?;
+        }
+        if self.nonstandard {
+            f.write_str("!")?;
+        }
+        Ok(())
+    }
+}
+
 /// A range of possible versions of a gem.
 #[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -100,6 +309,12 @@ pub struct GemRangeSemver<'i> {
     pub version: &'i str,
 }
 
+impl std::fmt::Display for GemRangeSemver<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.semver_constraint, self.version)
+    }
+}
+
 /// Gem which has been locked and came from some particular source.
 #[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
@@ -108,6 +323,30 @@ pub struct Spec<'i> {
     pub gem_version: GemVersion<'i>,
     #[serde(borrow)]
     pub deps: Vec<GemRange<'i>>,
+    /// Checksum from the `CHECKSUMS` section, if the lockfile recorded one for this spec.
+    /// Not serialized, because it's already serialized under `checksums`.
+    #[serde(skip)]
+    pub checksum: Option<Checksum<'i>>,
+}
+
+impl Spec<'_> {
+    /// The platform this spec was built for, e.g. `x86_64-linux` for
+    /// `nokogiri (1.18.10-x86_64-linux)`, or `ruby` for pure-Ruby specs.
+    /// `None` if the version's platform suffix isn't a valid platform.
+    pub fn platform(&self) -> Option<Platform> {
+        match self.gem_version.platform() {
+            None => Some(Platform::Ruby),
+            Some(platform) => Platform::new(platform).ok(),
+        }
+    }
+
+    /// Can this spec be installed on the given platform?
+    /// Pure-Ruby specs only match the `ruby` platform, see
+    /// [`GemfileDotLock::gem_specs_for_platform`] for falling back to them.
+    pub fn matches_platform(&self, platform: &Platform) -> bool {
+        self.platform()
+            .is_some_and(|spec_platform| spec_platform.matches(platform))
+    }
 }
 
 /// Checksum of a particular gem version.
@@ -149,3 +388,17 @@ pub enum SemverConstraint {
     /// `~>`
     Pessimistic,
 }
+
+impl std::fmt::Display for SemverConstraint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SemverConstraint::Exact => "=",
+            SemverConstraint::NotEqual => "!=",
+            SemverConstraint::GreaterThan => ">",
+            SemverConstraint::LessThan => "<",
+            SemverConstraint::GreaterThanOrEqual => ">=",
+            SemverConstraint::LessThanOrEqual => "<=",
+            SemverConstraint::Pessimistic => "~>",
+        })
+    }
+}
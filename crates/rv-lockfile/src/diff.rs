@@ -0,0 +1,129 @@
+//! Compares the specs locked in two lockfiles.
+
+use std::collections::BTreeMap;
+use std::fmt::{self, Display, Formatter};
+
+use crate::datatypes::{GemVersion, GemfileDotLock};
+
+/// The specs that were added, removed or changed between two lockfiles.
+/// Each list is sorted by gem name, then platform.
+#[derive(Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LockfileDiff<'i> {
+    pub added: Vec<LockedGem<'i>>,
+    pub removed: Vec<LockedGem<'i>>,
+    pub changed: Vec<VersionChange<'i>>,
+}
+
+/// A gem which is only locked in one of the two lockfiles.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LockedGem<'i> {
+    pub name: &'i str,
+    pub platform: Option<&'i str>,
+    pub version: &'i str,
+}
+
+impl<'i> From<GemVersion<'i>> for LockedGem<'i> {
+    fn from(gem_version: GemVersion<'i>) -> Self {
+        Self {
+            name: gem_version.name,
+            platform: gem_version.platform(),
+            version: gem_version.number(),
+        }
+    }
+}
+
+/// A gem which is locked to a different version in the new lockfile.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct VersionChange<'i> {
+    pub name: &'i str,
+    pub platform: Option<&'i str>,
+    pub old: &'i str,
+    pub new: &'i str,
+}
+
+impl LockfileDiff<'_> {
+    /// Are both lockfiles locked to the same specs?
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Compare the specs from every source in `old` and `new`.
+/// Platform variants of a gem are compared separately.
+pub fn diff<'i>(old: &GemfileDotLock<'i>, new: &GemfileDotLock<'i>) -> LockfileDiff<'i> {
+    let old = versions_by_gem(old);
+    let new = versions_by_gem(new);
+    let mut diff = LockfileDiff::default();
+
+    for (&(name, platform), &old_version) in &old {
+        match new.get(&(name, platform)) {
+            None => diff.removed.push(old_version.into()),
+            Some(new_version) if new_version.number() != old_version.number() => {
+                diff.changed.push(VersionChange {
+                    name,
+                    platform,
+                    old: old_version.number(),
+                    new: new_version.number(),
+                })
+            }
+            Some(_) => {}
+        }
+    }
+    diff.added = new
+        .iter()
+        .filter(|(key, _)| !old.contains_key(key))
+        .map(|(_, version)| (*version).into())
+        .collect();
+
+    diff
+}
+
+type GemKey<'i> = (&'i str, Option<&'i str>);
+
+fn versions_by_gem<'i>(lockfile: &GemfileDotLock<'i>) -> BTreeMap<GemKey<'i>, GemVersion<'i>> {
+    lockfile
+        .specs()
+        .map(|spec| {
+            let gem_version = spec.gem_version.clone();
+            ((gem_version.name, gem_version.platform()), gem_version)
+        })
+        .collect()
+}
+
+impl Display for LockfileDiff<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for gem_version in &self.added {
+            writeln!(f, "+ {gem_version}")?;
+        }
+        for gem_version in &self.removed {
+            writeln!(f, "- {gem_version}")?;
+        }
+        for change in &self.changed {
+            writeln!(f, "~ {change}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for LockedGem<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({}", self.name, self.version)?;
+        if let Some(platform) = self.platform {
+            write!(f, "-{platform}")?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl Display for VersionChange<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({} -> {})", self.name, self.old, self.new)?;
+        if let Some(platform) = self.platform {
+            write!(f, " [{platform}]")?;
+        }
+        Ok(())
+    }
+}
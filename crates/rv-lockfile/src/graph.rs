@@ -0,0 +1,155 @@
+//! Which locked gems depend on which, for explaining why a gem is locked or finding unused ones.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::datatypes::GemfileDotLock;
+
+/// The dependencies between the gems in a lockfile, by name.
+/// Platform variants of the same gem are merged into one node.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DependencyGraph<'i> {
+    /// The direct dependencies of each locked gem. These can include gems which aren't locked,
+    /// like `bundler`.
+    deps: BTreeMap<&'i str, BTreeSet<&'i str>>,
+    /// The gems listed in the `DEPENDENCIES` section, i.e. those in the Gemfile.
+    roots: BTreeSet<&'i str>,
+}
+
+impl<'i> GemfileDotLock<'i> {
+    /// Build the graph of which locked gems depend on which.
+    pub fn dependency_graph(&self) -> DependencyGraph<'i> {
+        let mut deps: BTreeMap<&'i str, BTreeSet<&'i str>> = BTreeMap::new();
+        for spec in self.specs() {
+            deps.entry(spec.gem_version.name)
+                .or_default()
+                .extend(spec.deps.iter().map(|dep| dep.name));
+        }
+        let roots = self.dependencies.iter().map(|dep| dep.name).collect();
+        DependencyGraph { deps, roots }
+    }
+}
+
+impl<'i> DependencyGraph<'i> {
+    /// The gems listed in the `DEPENDENCIES` section, sorted by name.
+    pub fn roots(&self) -> impl Iterator<Item = &'i str> + '_ {
+        self.roots.iter().copied()
+    }
+
+    /// Is this gem listed in the `DEPENDENCIES` section?
+    pub fn is_root(&self, name: &str) -> bool {
+        self.roots.contains(name)
+    }
+
+    /// The gems `name` depends on directly, sorted by name.
+    pub fn direct_deps(&self, name: &str) -> impl Iterator<Item = &'i str> + '_ {
+        self.deps.get(name).into_iter().flatten().copied()
+    }
+
+    /// Every gem `name` depends on, directly or through other gems. A gem is only included in
+    /// its own dependencies if it's part of a cycle.
+    pub fn transitive_deps(&self, name: &str) -> BTreeSet<&'i str> {
+        let mut seen = BTreeSet::new();
+        let mut stack: Vec<&'i str> = self.direct_deps(name).collect();
+        while let Some(dep) = stack.pop() {
+            if seen.insert(dep) {
+                stack.extend(self.direct_deps(dep));
+            }
+        }
+        seen
+    }
+
+    /// The gems which depend on `name` directly, sorted by name.
+    pub fn reverse_deps(&self, name: &str) -> BTreeSet<&'i str> {
+        self.deps
+            .iter()
+            .filter(|(_, deps)| deps.contains(name))
+            .map(|(&gem, _)| gem)
+            .collect()
+    }
+
+    /// Every chain of dependencies from a gem in the `DEPENDENCIES` section down to `name`, like
+    /// `["rails", "actionpack", "rack"]`, sorted. If `name` is itself in the `DEPENDENCIES`
+    /// section, the chain `[name]` is included. Chains never visit the same gem twice.
+    pub fn paths_to(&self, name: &str) -> Vec<Vec<&'i str>> {
+        // Only gems which lead to `name` are searched, so the rest of a large lockfile doesn't
+        // have to be walked path by path.
+        let mut leads_to = BTreeSet::from([name]);
+        let mut stack = vec![name];
+        while let Some(gem) = stack.pop() {
+            for dependent in self.reverse_deps(gem) {
+                if leads_to.insert(dependent) {
+                    stack.push(dependent);
+                }
+            }
+        }
+
+        let mut paths = Vec::new();
+        for root in self.roots().filter(|root| leads_to.contains(root)) {
+            let mut path = vec![root];
+            self.collect_paths_to(name, &leads_to, &mut path, &mut paths);
+        }
+        paths.sort();
+        paths
+    }
+
+    fn collect_paths_to(
+        &self,
+        name: &str,
+        leads_to: &BTreeSet<&str>,
+        path: &mut Vec<&'i str>,
+        paths: &mut Vec<Vec<&'i str>>,
+    ) {
+        let gem = *path.last().expect("paths start at a root");
+        if gem == name {
+            paths.push(path.clone());
+            return;
+        }
+        for dep in self.direct_deps(gem) {
+            if leads_to.contains(dep) && !path.contains(&dep) {
+                path.push(dep);
+                self.collect_paths_to(name, leads_to, path, paths);
+                path.pop();
+            }
+        }
+    }
+
+    /// Find a dependency cycle, if there is one. It's returned as the path around the cycle,
+    /// starting and ending with the same gem, e.g. `["a", "b", "a"]`.
+    pub fn find_cycle(&self) -> Option<Vec<&'i str>> {
+        // Gems whose dependencies have all been searched already.
+        let mut done = BTreeSet::new();
+        for &start in self.deps.keys() {
+            let mut path = Vec::new();
+            if let Some(cycle) = self.find_cycle_from(start, &mut path, &mut done) {
+                return Some(cycle);
+            }
+        }
+        None
+    }
+
+    fn find_cycle_from(
+        &self,
+        gem: &'i str,
+        path: &mut Vec<&'i str>,
+        done: &mut BTreeSet<&'i str>,
+    ) -> Option<Vec<&'i str>> {
+        if let Some(position) = path.iter().position(|&on_path| on_path == gem) {
+            let mut cycle = path[position..].to_vec();
+            cycle.push(gem);
+            return Some(cycle);
+        }
+        if done.contains(gem) {
+            return None;
+        }
+
+        path.push(gem);
+        for dep in self.direct_deps(gem) {
+            if let Some(cycle) = self.find_cycle_from(dep, path, done) {
+                return Some(cycle);
+            }
+        }
+        path.pop();
+        done.insert(gem);
+        None
+    }
+}
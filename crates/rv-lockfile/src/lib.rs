@@ -1,8 +1,13 @@
-mod datatypes;
+pub mod datatypes;
+pub mod diff;
+pub mod graph;
 pub mod parser;
+mod serializer;
 #[cfg(test)]
 mod tests;
+pub mod validate;
 
+pub use diff::diff;
 use miette::{Diagnostic, SourceSpan};
 pub use parser::parse;
 
@@ -19,6 +24,17 @@ pub struct ParseErrors {
     pub others: Vec<ParseError>,
 }
 
+impl ParseErrors {
+    /// Where each of [`ParseErrors::others`] starts and ends in the lockfile, for tools like
+    /// editors which place diagnostics by line and column rather than byte offset.
+    pub fn line_columns(&self) -> Vec<(LineColumn, LineColumn)> {
+        self.others
+            .iter()
+            .map(|error| error.line_columns(&self.lockfile_contents))
+            .collect()
+    }
+}
+
 #[derive(Debug, thiserror::Error, Diagnostic)]
 #[error("Could not parse: {msg}")]
 #[diagnostic()]
@@ -30,3 +46,43 @@ pub struct ParseError {
     /// Error message
     msg: String,
 }
+
+impl ParseError {
+    /// The byte range of the lockfile where parsing failed.
+    pub fn span(&self) -> SourceSpan {
+        self.char_offset
+    }
+
+    /// Where this error starts and ends in `lockfile_contents`, the lockfile it came from.
+    pub fn line_columns(&self, lockfile_contents: &str) -> (LineColumn, LineColumn) {
+        let start = self.char_offset.offset();
+        (
+            LineColumn::at(lockfile_contents, start),
+            LineColumn::at(lockfile_contents, start + self.char_offset.len()),
+        )
+    }
+}
+
+/// A position in a lockfile. Both the line and column start at 1, and columns count characters,
+/// not bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LineColumn {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl LineColumn {
+    /// The position of the character at `byte_offset` in `text`.
+    fn at(text: &str, byte_offset: usize) -> Self {
+        let mut byte_offset = byte_offset.min(text.len());
+        while !text.is_char_boundary(byte_offset) {
+            byte_offset -= 1;
+        }
+        let before = &text[..byte_offset];
+        let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+        Self {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+}
@@ -1,12 +1,16 @@
 use crate::{ParseError, ParseErrors, datatypes::*};
 use miette::SourceSpan;
+use std::collections::HashMap;
+use std::io::BufRead;
 use winnow::{
     LocatingSlice, ModalResult, Parser,
-    ascii::{line_ending, space0, space1},
-    combinator::{alt, delimited, dispatch, opt, peek, preceded, repeat, separated, terminated},
+    ascii::{alpha1, digit0, digit1, line_ending, space0, space1},
+    combinator::{
+        alt, cut_err, delimited, dispatch, eof, opt, peek, preceded, repeat, separated, terminated,
+    },
     error::{ContextError, ErrMode},
     stream::{AsChar, Location, Stream},
-    token::{take_until, take_while},
+    token::{take_till, take_while},
 };
 
 const GIT: &str = "GIT";
@@ -27,9 +31,9 @@ enum Section<'i> {
     Git(GitSection<'i>),
     Gem(GemSection<'i>),
     Path(PathSection<'i>),
-    Platforms(Vec<&'i str>),
+    Platforms(Vec<Platform>),
     Dependencies(Vec<GemRange<'i>>),
-    RubyVersion(&'i str),
+    RubyVersion(RubyVersion<'i>),
     BundledWith(&'i str),
     Checksums(Vec<Checksum<'i>>),
 }
@@ -60,8 +64,7 @@ pub fn parse<'i>(file: &'i str) -> Result<GemfileDotLock<'i>, ParseErrors> {
             Ok(sec) => sec,
             Err(e) => {
                 // OK, there was an error. Let's figure out where, to highlight it.
-                let byte_offset = i.location();
-                let char_offset = file[..byte_offset.min(file.len())].chars().count();
+                let byte_offset = i.location().min(file.len());
 
                 // Then find the error message.
                 let msg = match &e {
@@ -71,7 +74,7 @@ pub fn parse<'i>(file: &'i str) -> Result<GemfileDotLock<'i>, ParseErrors> {
 
                 // Now we can add the error to the list.
                 let parse_err = ParseError {
-                    char_offset: SourceSpan::new(char_offset.into(), 1),
+                    char_offset: token_span(file, byte_offset),
                     msg,
                 };
                 if let Some(err) = error.as_mut() {
@@ -149,11 +152,160 @@ pub fn parse<'i>(file: &'i str) -> Result<GemfileDotLock<'i>, ParseErrors> {
     }
 
     match error {
-        None => Ok(parsed),
+        None => {
+            attach_checksums(&mut parsed);
+            Ok(parsed)
+        }
         Some(error) => Err(error),
     }
 }
 
+/// The span of the token starting at `byte_offset`, which runs up to the closing paren or the
+/// end of its line. Spans are in bytes, like everything else miette works with.
+fn token_span(text: &str, byte_offset: usize) -> SourceSpan {
+    let rest = &text[byte_offset..];
+    let len = rest.find([')', '\r', '\n']).unwrap_or(rest.len()).max(1);
+    SourceSpan::new(byte_offset.into(), len)
+}
+
+/// Read the specs from every `GIT`, `PATH` and `GEM` section of a lockfile, one at a time.
+///
+/// Unlike [`parse`], this never holds more than one spec's text in memory, so it suits very
+/// large lockfiles. Other sections are skipped, so checksums aren't attached to the specs.
+pub fn parse_specs<R: BufRead>(reader: R) -> impl Iterator<Item = Result<SpecText, ParseError>> {
+    SpecTexts {
+        reader,
+        in_specs: false,
+        pending: None,
+        offset: 0,
+    }
+}
+
+/// The text of a single spec from a lockfile, read by [`parse_specs`].
+#[derive(Debug, Clone)]
+pub struct SpecText {
+    text: String,
+}
+
+impl SpecText {
+    /// The parsed spec, borrowing from this text.
+    pub fn spec(&self) -> Spec<'_> {
+        parse_spec
+            .parse(LocatingSlice::new(self.text.as_str()))
+            .expect("spec text is validated when it's read")
+    }
+}
+
+struct SpecTexts<R> {
+    reader: R,
+    /// Are we inside the `specs:` list of a source section?
+    in_specs: bool,
+    /// The spec being read, and the byte offset where it starts.
+    pending: Option<(String, usize)>,
+    /// The byte offset of the next line.
+    offset: usize,
+}
+
+impl<R: BufRead> SpecTexts<R> {
+    /// Finish reading the pending spec, if there is one.
+    fn flush(&mut self) -> Option<Result<SpecText, ParseError>> {
+        let (text, offset) = self.pending.take()?;
+        match parse_spec.parse(LocatingSlice::new(text.as_str())) {
+            Ok(_) => Some(Ok(SpecText { text })),
+            Err(e) => Some(Err(ParseError {
+                char_offset: {
+                    let span = token_span(&text, e.offset());
+                    SourceSpan::new((offset + span.offset()).into(), span.len())
+                },
+                msg: e.inner().to_string(),
+            })),
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for SpecTexts<R> {
+    type Item = Result<SpecText, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            // Lines are kept with their line endings, whether `\n` or `\r\n`, so that offsets into
+            // the spec's text line up with the lockfile.
+            let mut raw = String::new();
+            match self.reader.read_line(&mut raw) {
+                Ok(0) => return self.flush(),
+                Ok(_) => {}
+                Err(e) => {
+                    return Some(Err(ParseError {
+                        char_offset: SourceSpan::new(self.offset.into(), 0),
+                        msg: e.to_string(),
+                    }));
+                }
+            }
+            let offset = self.offset;
+            self.offset += raw.len();
+            if !raw.ends_with('\n') {
+                raw.push('\n');
+            }
+            let line = raw.trim_end_matches('\n').trim_end_matches('\r');
+
+            if !self.in_specs {
+                self.in_specs = line == "  specs:";
+                continue;
+            }
+            if line.starts_with("      ")
+                && let Some((text, _)) = self.pending.as_mut()
+            {
+                text.push_str(&raw);
+                continue;
+            }
+
+            let done = self.flush();
+            if line.starts_with("    ") {
+                self.pending = Some((raw, offset));
+            } else {
+                self.in_specs = false;
+            }
+            if done.is_some() {
+                return done;
+            }
+        }
+    }
+}
+
+/// Copy each checksum from the `CHECKSUMS` section onto the spec it describes.
+fn attach_checksums(lockfile: &mut GemfileDotLock<'_>) {
+    let Some(checksums) = &lockfile.checksums else {
+        return;
+    };
+    let by_gem_version: HashMap<_, _> = checksums
+        .iter()
+        .filter(|checksum| checksum.algorithm != ChecksumAlgorithm::None)
+        .map(|checksum| (&checksum.gem_version, checksum))
+        .collect();
+
+    let specs = lockfile
+        .git
+        .iter_mut()
+        .flat_map(|section| section.specs.iter_mut())
+        .chain(
+            lockfile
+                .gem
+                .iter_mut()
+                .flat_map(|section| section.specs.iter_mut()),
+        )
+        .chain(
+            lockfile
+                .path
+                .iter_mut()
+                .flat_map(|section| section.specs.iter_mut()),
+        );
+    for spec in specs {
+        spec.checksum = by_gem_version
+            .get(&spec.gem_version)
+            .map(|checksum| (*checksum).clone());
+    }
+}
+
 /// Parse a paragraph, i.e. something ending in a new line.
 fn paragraph<'i, O, F>(parser: F) -> impl Parser<Input<'i>, O, ContextError>
 where
@@ -185,10 +337,16 @@ fn parse_spec<'i>(i: &mut Input<'i>) -> Res<Spec<'i>> {
 fn parse_spec_no_delimiters<'i>(i: &mut Input<'i>) -> Res<Spec<'i>> {
     let name = parse_gem_name.parse_next(i)?;
     space1.parse_next(i)?;
-    let version = delimited('(', parse_version, ")\n").parse_next(i)?;
+    // Once we've seen the opening paren, this can only be a spec, so point at the bad version.
+    '('.parse_next(i)?;
+    let version = cut_err(terminated(parse_version, (')', line_ending))).parse_next(i)?;
     let gem_version = GemVersion { name, version };
     let deps = repeat(0.., parse_spec_dep).parse_next(i)?;
-    Ok(Spec { gem_version, deps })
+    Ok(Spec {
+        gem_version,
+        deps,
+        checksum: None,
+    })
 }
 
 fn parse_spec_dep<'i>(i: &mut Input<'i>) -> Res<GemRange<'i>> {
@@ -250,18 +408,63 @@ fn parse_gem_name<'i>(i: &mut Input<'i>) -> Res<&'i str> {
     .parse_next(i)
 }
 
-fn parse_bundled_with_contents<'i>(i: &mut Input<'i>) -> Res<&'i str> {
-    take_while(0.., |c: char| {
-        c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.'
-    })
-    .parse_next(i)
+/// Parses e.g. `ruby 3.3.4p94` or `ruby 3.1.4p0 (jruby 9.4.0.0)`.
+/// Also accepts the engine first, e.g. `jruby 9.4.0.0 (ruby 3.1.4)`.
+fn parse_ruby_version_contents<'i>(i: &mut Input<'i>) -> Res<RubyVersion<'i>> {
+    (
+        parse_engine_version,
+        opt(preceded(space1, delimited('(', parse_engine_version, ')'))),
+    )
+        .verify_map(|(first, second)| match (first, second) {
+            (("ruby", version, patchlevel), None) => Some(RubyVersion {
+                engine: "ruby",
+                engine_version: None,
+                version,
+                patchlevel,
+            }),
+            (("ruby", version, patchlevel), Some((engine, engine_version, _))) => {
+                Some(RubyVersion {
+                    engine,
+                    engine_version: Some(engine_version),
+                    version,
+                    patchlevel,
+                })
+            }
+            ((engine, engine_version, _), Some(("ruby", version, patchlevel))) => {
+                Some(RubyVersion {
+                    engine,
+                    engine_version: Some(engine_version),
+                    version,
+                    patchlevel,
+                })
+            }
+            _ => None,
+        })
+        .parse_next(i)
 }
 
-fn parse_ruby_version_contents<'i>(i: &mut Input<'i>) -> Res<&'i str> {
-    take_while(0.., |c: char| {
-        c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.' || c == ' '
-    })
-    .parse_next(i)
+/// Parses an engine name, its version and optional patchlevel, e.g. `ruby 3.3.4p94`.
+fn parse_engine_version<'i>(i: &mut Input<'i>) -> Res<(&'i str, &'i str, Option<&'i str>)> {
+    let engine = take_while(1.., |c: char| c.is_ascii_alphanumeric() || c == '_').parse_next(i)?;
+    space1.parse_next(i)?;
+    // Numeric segments can't contain letters, so that the patchlevel isn't swallowed,
+    // but prerelease segments like `preview1` can contain digits.
+    let version = (
+        digit1,
+        repeat::<_, _, (), _, _>(0.., ('.', alt((digit1, (alpha1, digit0).take())))),
+    )
+        .take()
+        .parse_next(i)?;
+    // Development builds of Ruby have a patchlevel of -1.
+    let patchlevel = opt(preceded('p', (opt('-'), digit1).take())).parse_next(i)?;
+    Ok((engine, version, patchlevel))
+}
+
+/// Parse the contents of a `RUBY VERSION` section, e.g. `ruby 3.3.4p94`.
+pub(crate) fn parse_ruby_version_str(s: &str) -> Option<RubyVersion<'_>> {
+    parse_ruby_version_contents
+        .parse(LocatingSlice::new(s))
+        .ok()
 }
 
 fn alphanumdash<'i>(i: &mut Input<'i>) -> Res<&'i str> {
@@ -303,20 +506,28 @@ fn parse_checksum<'i>(i: &mut Input<'i>) -> Res<Checksum<'i>> {
     '('.parse_next(i)?;
     let version = parse_version.parse_next(i)?;
     ')'.parse_next(i)?;
-    let value = opt((space1, "sha256=")).parse_next(i)?;
-    if value.is_some() {
-        let sha256 = parse_hex_string.try_map(hex::decode).parse_next(i)?;
-        Ok(Checksum {
-            gem_version: GemVersion { name, version },
+    let gem_version = GemVersion { name, version };
+
+    // From here on this is definitely a checksum line, so report any problem
+    // exactly where it happens instead of backtracking to the start of the line.
+    let sha256 = opt(preceded(
+        space1,
+        cut_err(preceded("sha256=", parse_hex_string.try_map(hex::decode))),
+    ))
+    .parse_next(i)?;
+    cut_err(line_ending).parse_next(i)?;
+
+    match sha256 {
+        Some(sha256) => Ok(Checksum {
+            gem_version,
             value: sha256,
             algorithm: ChecksumAlgorithm::SHA256,
-        })
-    } else {
-        Ok(Checksum {
-            gem_version: GemVersion { name, version },
+        }),
+        None => Ok(Checksum {
+            gem_version,
             value: vec![],
             algorithm: ChecksumAlgorithm::None,
-        })
+        }),
     }
 }
 
@@ -327,76 +538,99 @@ fn parse_num(i: &mut Input<'_>) -> Res<u32> {
 }
 
 fn parse_git_section<'i>(i: &mut Input<'i>) -> Res<GitSection<'i>> {
-    "GIT\n".parse_next(i)?;
+    ("GIT", line_ending).parse_next(i)?;
     let remote = delimited("  remote: ", parse_remote, line_ending).parse_next(i)?;
     let revision = delimited("  revision: ", parse_hex_string, line_ending).parse_next(i)?;
-    let branch = opt(delimited(
-        "  branch: ",
-        take_while(1.., |c: char| {
-            c.is_alphanumeric() || c == '.' || c == '-' || c == '_' || c == '/'
-        }),
-        line_ending,
-    ))
-    .parse_next(i)?;
-    "  specs:\n".parse_next(i)?;
+    // Bundler writes these options in this order, each only if it was set.
+    let git_ref = opt(delimited("  ref: ", parse_git_ref_name, line_ending)).parse_next(i)?;
+    let branch = opt(delimited("  branch: ", parse_git_ref_name, line_ending)).parse_next(i)?;
+    let tag = opt(delimited("  tag: ", parse_git_ref_name, line_ending)).parse_next(i)?;
+    ("  specs:", line_ending).parse_next(i)?;
     let specs = repeat(0.., parse_spec).parse_next(i)?;
     Ok(GitSection {
-        branch,
         remote,
         revision,
+        git_ref,
+        branch,
+        tag,
         specs,
     })
 }
 
-fn parse_platforms<'i>(i: &mut Input<'i>) -> Res<Vec<&'i str>> {
-    "PLATFORMS\n".parse_next(i)?;
-    repeat(1.., delimited(space1, parse_gem_name, line_ending)).parse_next(i)
+/// A branch, tag or other ref name in a Git repo.
+fn parse_git_ref_name<'i>(i: &mut Input<'i>) -> Res<&'i str> {
+    take_while(1.., |c: char| {
+        c.is_alphanumeric() || c == '.' || c == '-' || c == '_' || c == '/' || c == '+'
+    })
+    .parse_next(i)
+}
+
+fn parse_platforms(i: &mut Input<'_>) -> Res<Vec<Platform>> {
+    ("PLATFORMS", line_ending).parse_next(i)?;
+    repeat(
+        1..,
+        delimited(space1, parse_gem_name.try_map(Platform::new), line_ending),
+    )
+    .parse_next(i)
 }
 
 fn parse_dependencies<'i>(i: &mut Input<'i>) -> Res<Vec<GemRange<'i>>> {
-    "DEPENDENCIES\n".parse_next(i)?;
+    ("DEPENDENCIES", line_ending).parse_next(i)?;
     repeat(0.., delimited(space1, parse_dependency, line_ending)).parse_next(i)
 }
 
 fn parse_checksums<'i>(i: &mut Input<'i>) -> Res<Vec<Checksum<'i>>> {
-    "CHECKSUMS\n".parse_next(i)?;
-    repeat(0.., delimited(space1, parse_checksum, line_ending)).parse_next(i)
+    ("CHECKSUMS", line_ending).parse_next(i)?;
+    repeat(0.., preceded(space1, parse_checksum)).parse_next(i)
 }
 
 fn parse_bundled_with<'i>(i: &mut Input<'i>) -> Res<&'i str> {
     "BUNDLED WITH".parse_next(i)?;
     space0.parse_next(i)?;
-    "\n".parse_next(i)?;
-    preceded(space1, parse_bundled_with_contents).parse_next(i)
+    line_ending.parse_next(i)?;
+    space1.parse_next(i)?;
+
+    // The whole line must be a version. If it isn't, point the error at the start of the line.
+    let start = i.checkpoint();
+    terminated(parse_version, (space0, peek(alt((line_ending, eof)))))
+        .parse_next(i)
+        .map_err(|e| {
+            i.reset(&start);
+            e.cut()
+        })
 }
 
-fn parse_ruby_version<'i>(i: &mut Input<'i>) -> Res<&'i str> {
+fn parse_ruby_version<'i>(i: &mut Input<'i>) -> Res<RubyVersion<'i>> {
     "RUBY VERSION".parse_next(i)?;
     space0.parse_next(i)?;
-    "\n".parse_next(i)?;
+    line_ending.parse_next(i)?;
     preceded(space1, terminated(parse_ruby_version_contents, line_ending)).parse_next(i)
 }
 
 fn parse_gem<'i>(i: &mut Input<'i>) -> Res<GemSection<'i>> {
-    "GEM\n".parse_next(i)?;
-    let remote = opt(delimited("  remote: ", parse_remote, line_ending))
-        .parse_next(i)?
-        .unwrap_or("http://rubygems.org");
-    "  specs:\n".parse_next(i)?;
+    ("GEM", line_ending).parse_next(i)?;
+    // Newer versions of Bundler can list several remotes for one section.
+    let mut remotes: Vec<_> =
+        repeat(0.., delimited("  remote: ", parse_remote, line_ending)).parse_next(i)?;
+    if remotes.is_empty() {
+        remotes.push("http://rubygems.org");
+    }
+    ("  specs:", line_ending).parse_next(i)?;
     let specs = repeat(0.., parse_spec).parse_next(i)?;
-    Ok(GemSection { remote, specs })
+    Ok(GemSection { remotes, specs })
 }
 
 fn parse_path<'i>(i: &mut Input<'i>) -> Res<PathSection<'i>> {
-    "PATH\n".parse_next(i)?;
+    ("PATH", line_ending).parse_next(i)?;
     let remote = delimited("  remote: ", parse_remote, line_ending).parse_next(i)?;
-    "  specs:\n".parse_next(i)?;
+    ("  specs:", line_ending).parse_next(i)?;
     let specs = repeat(0.., parse_spec).parse_next(i)?;
     Ok(PathSection { remote, specs })
 }
 
+/// Everything up to the end of the line, not including a `\r` before the `\n`.
 fn parse_remote<'i>(i: &mut Input<'i>) -> Res<&'i str> {
-    take_until(0.., '\n').parse_next(i)
+    take_till(0.., ['\r', '\n']).parse_next(i)
 }
 
 #[cfg(test)]
@@ -467,6 +701,61 @@ GEM
         }
     }
 
+    #[test]
+    fn test_parse_dependencies() {
+        let input = "\
+DEPENDENCIES
+  activerecord-gitlab!
+  bundler-checksum (~> 0.1.0)!
+  nokogiri (>= 1.15.7, != 1.16.7)
+  rails (~> 7.1.0)
+  rake
+";
+        let i = LocatingSlice::new(input);
+        let deps = parse_dependencies.parse(i).unwrap();
+        assert_eq!(deps.len(), 5);
+
+        // Pinned to a non-RubyGems source, without any constraint.
+        assert_eq!(deps[0].name, "activerecord-gitlab");
+        assert_eq!(deps[0].semver, None);
+        assert!(deps[0].nonstandard);
+
+        // Pinned, with a constraint.
+        assert_eq!(deps[1].name, "bundler-checksum");
+        assert!(deps[1].nonstandard);
+        assert_eq!(
+            deps[1].semver,
+            Some(vec![GemRangeSemver {
+                semver_constraint: SemverConstraint::Pessimistic,
+                version: "0.1.0",
+            }])
+        );
+
+        // Several constraints.
+        assert_eq!(
+            deps[2].semver,
+            Some(vec![
+                GemRangeSemver {
+                    semver_constraint: SemverConstraint::GreaterThanOrEqual,
+                    version: "1.15.7",
+                },
+                GemRangeSemver {
+                    semver_constraint: SemverConstraint::NotEqual,
+                    version: "1.16.7",
+                },
+            ])
+        );
+        assert!(!deps[2].nonstandard);
+
+        assert_eq!(deps[3].name, "rails");
+        assert!(!deps[3].nonstandard);
+
+        // Plain entry.
+        assert_eq!(deps[4].name, "rake");
+        assert_eq!(deps[4].semver, None);
+        assert!(!deps[4].nonstandard);
+    }
+
     #[test]
     fn test_ranges() {
         let input = " (>= 1.15.7, != 1.16.7, != 1.16.6, != 1.16.5, != 1.16.4, != 1.16.3, != 1.16.2, != 1.16.1, != 1.16.0.rc1, != 1.16.0)";
@@ -526,6 +815,40 @@ GIT
         }
     }
 
+    #[test]
+    fn test_git_section_options() {
+        let input = "\
+GIT
+  remote: https://github.com/rails/rails.git
+  revision: 0f1a2b3c4d5e6f708192a3b4c5d6e7f8091a2b3c
+  ref: 0f1a2b3c
+  branch: 8-0-stable
+  tag: v8.0.2
+  specs:
+    rails (8.0.2)
+";
+        let i = LocatingSlice::new(input);
+        let git_section = parse_git_section.parse(i).unwrap();
+        assert_eq!(git_section.git_ref, Some("0f1a2b3c"));
+        assert_eq!(git_section.branch, Some("8-0-stable"));
+        assert_eq!(git_section.tag, Some("v8.0.2"));
+        assert_eq!(git_section.specs.len(), 1);
+
+        let input = "\
+GIT
+  remote: https://github.com/rails/rails.git
+  revision: 0f1a2b3c4d5e6f708192a3b4c5d6e7f8091a2b3c
+  tag: v8.0.2
+  specs:
+    rails (8.0.2)
+";
+        let i = LocatingSlice::new(input);
+        let git_section = parse_git_section.parse(i).unwrap();
+        assert_eq!(git_section.git_ref, None);
+        assert_eq!(git_section.branch, None);
+        assert_eq!(git_section.tag, Some("v8.0.2"));
+    }
+
     #[test]
     fn test_parse_path() {
         let input = "\
@@ -538,6 +861,49 @@ PATH
         parse_path.parse(i).unwrap();
     }
 
+    #[test]
+    fn test_parse_path_relative_to_parent() {
+        let input = "\
+PATH
+  remote: ../my_gem
+  specs:
+    my_gem (0.2.0)
+      rack (>= 2.0)
+";
+        let i = LocatingSlice::new(input);
+        let path_section = parse_path.parse(i).unwrap();
+        assert_eq!(path_section.remote, "../my_gem");
+        assert_eq!(path_section.specs.len(), 1);
+        assert_eq!(path_section.specs[0].gem_version.name, "my_gem");
+        assert_eq!(path_section.specs[0].deps[0].name, "rack");
+    }
+
+    #[test]
+    fn test_parse_gem_with_multiple_remotes() {
+        let input = "\
+GEM
+  remote: https://gem.coop/
+  remote: https://rubygems.org/
+  specs:
+    rack (3.2.3)
+";
+        let i = LocatingSlice::new(input);
+        let gem_section = parse_gem.parse(i).unwrap();
+        assert_eq!(
+            gem_section.remotes,
+            vec!["https://gem.coop/", "https://rubygems.org/"]
+        );
+        assert_eq!(gem_section.specs.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_gem_without_remote() {
+        let input = "GEM\n  specs:\n    rack (3.2.3)\n";
+        let i = LocatingSlice::new(input);
+        let gem_section = parse_gem.parse(i).unwrap();
+        assert_eq!(gem_section.remotes, vec!["http://rubygems.org"]);
+    }
+
     #[test]
     fn test_parse_section_header() {
         let input = "\
@@ -551,6 +917,45 @@ PATH
         assert_eq!(actual, "PATH");
     }
 
+    #[test]
+    fn test_parse_ruby_version() {
+        let input = "RUBY VERSION\n   ruby 3.3.4p60\n";
+        let i = LocatingSlice::new(input);
+        let ruby_version = parse_ruby_version.parse(i).unwrap();
+        assert_eq!(
+            ruby_version,
+            RubyVersion {
+                engine: "ruby",
+                engine_version: None,
+                version: "3.3.4",
+                patchlevel: Some("60"),
+            }
+        );
+        assert_eq!(ruby_version.to_string(), "ruby 3.3.4p60");
+
+        let expected = RubyVersion {
+            engine: "jruby",
+            engine_version: Some("9.4.0.0"),
+            version: "3.1.4",
+            patchlevel: None,
+        };
+        for input in [
+            "RUBY VERSION\n   jruby 9.4.0.0 (ruby 3.1.4)\n",
+            "RUBY VERSION\n   ruby 3.1.4 (jruby 9.4.0.0)\n",
+        ] {
+            let i = LocatingSlice::new(input);
+            let ruby_version = parse_ruby_version.parse(i).unwrap();
+            assert_eq!(ruby_version, expected);
+        }
+        assert_eq!(expected.to_string(), "ruby 3.1.4 (jruby 9.4.0.0)");
+
+        let input = "RUBY VERSION\n   ruby 3.5.0.preview1p-1\n";
+        let i = LocatingSlice::new(input);
+        let ruby_version = parse_ruby_version.parse(i).unwrap();
+        assert_eq!(ruby_version.version, "3.5.0.preview1");
+        assert_eq!(ruby_version.patchlevel, Some("-1"));
+    }
+
     #[test]
     fn test_parse_version() {
         for input in [
@@ -0,0 +1,130 @@
+//! Writes a [`GemfileDotLock`] back out as text, in the same format Bundler uses.
+
+use std::fmt::{self, Display, Formatter, Write};
+
+use crate::datatypes::*;
+
+impl Display for GemfileDotLock<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut out = SectionWriter { f, empty: true };
+
+        for section in &self.git {
+            let f = out.section("GIT")?;
+            writeln!(f, "  remote: {}", section.remote)?;
+            writeln!(f, "  revision: {}", section.revision)?;
+            if let Some(git_ref) = section.git_ref {
+                writeln!(f, "  ref: {git_ref}")?;
+            }
+            if let Some(branch) = section.branch {
+                writeln!(f, "  branch: {branch}")?;
+            }
+            if let Some(tag) = section.tag {
+                writeln!(f, "  tag: {tag}")?;
+            }
+            write_specs(f, &section.specs)?;
+        }
+
+        for section in &self.path {
+            let f = out.section("PATH")?;
+            writeln!(f, "  remote: {}", section.remote)?;
+            write_specs(f, &section.specs)?;
+        }
+
+        for section in &self.gem {
+            let f = out.section("GEM")?;
+            for remote in &section.remotes {
+                writeln!(f, "  remote: {remote}")?;
+            }
+            write_specs(f, &section.specs)?;
+        }
+
+        if !self.platforms.is_empty() {
+            let f = out.section("PLATFORMS")?;
+            let mut platforms: Vec<_> = self.platforms.iter().map(Platform::to_string).collect();
+            platforms.sort_unstable();
+            for platform in platforms {
+                writeln!(f, "  {platform}")?;
+            }
+        }
+
+        if !self.dependencies.is_empty() {
+            let f = out.section("DEPENDENCIES")?;
+            let mut dependencies: Vec<_> = self.dependencies.iter().collect();
+            dependencies.sort_by_key(|dep| dep.name);
+            for dep in dependencies {
+                writeln!(f, "  {dep}")?;
+            }
+        }
+
+        if let Some(checksums) = &self.checksums {
+            let f = out.section("CHECKSUMS")?;
+            let mut checksums: Vec<_> = checksums.iter().collect();
+            checksums.sort_by_cached_key(|checksum| full_name(&checksum.gem_version));
+            for checksum in checksums {
+                write!(f, "  {}", checksum.gem_version)?;
+                match checksum.algorithm {
+                    ChecksumAlgorithm::None => {}
+                    ChecksumAlgorithm::SHA256 => {
+                        write!(f, " sha256={}", hex::encode(&checksum.value))?
+                    }
+                    ChecksumAlgorithm::Unknown(algorithm) => {
+                        write!(f, " {algorithm}={}", hex::encode(&checksum.value))?
+                    }
+                }
+                f.write_char('\n')?;
+            }
+        }
+
+        if let Some(ruby_version) = &self.ruby_version {
+            let f = out.section("RUBY VERSION")?;
+            writeln!(f, "   {ruby_version}")?;
+        }
+
+        if let Some(bundled_with) = self.bundled_with {
+            let f = out.section("BUNDLED WITH")?;
+            writeln!(f, "   {bundled_with}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes sections separated by blank lines.
+struct SectionWriter<'a, 'b> {
+    f: &'a mut Formatter<'b>,
+    empty: bool,
+}
+
+impl<'b> SectionWriter<'_, 'b> {
+    /// Start a new section with the given header.
+    fn section(&mut self, header: &str) -> Result<&mut Formatter<'b>, fmt::Error> {
+        if !self.empty {
+            self.f.write_char('\n')?;
+        }
+        self.empty = false;
+        writeln!(self.f, "{header}")?;
+        Ok(&mut *self.f)
+    }
+}
+
+/// Like Bundler, sort specs by their full name, so that platform variants of the same
+/// gem version always come out in the same order. Their dependencies are sorted by name.
+fn write_specs(f: &mut Formatter<'_>, specs: &[Spec<'_>]) -> fmt::Result {
+    writeln!(f, "  specs:")?;
+    let mut specs: Vec<_> = specs.iter().collect();
+    specs.sort_by_cached_key(|spec| full_name(&spec.gem_version));
+    for spec in specs {
+        writeln!(f, "    {}", spec.gem_version)?;
+        let mut deps: Vec<_> = spec.deps.iter().collect();
+        deps.sort_by_key(|dep| dep.name);
+        for dep in deps {
+            writeln!(f, "      {dep}")?;
+        }
+    }
+    Ok(())
+}
+
+/// The name RubyGems gives a gem version, e.g. `nokogiri-1.18.10-arm64-darwin`.
+fn full_name(gem_version: &GemVersion<'_>) -> String {
+    format!("{}-{}", gem_version.name, gem_version.version)
+}
@@ -72,6 +72,435 @@ fn test_parse_withoutsource() {
     insta::assert_yaml_snapshot!(output);
 }
 
+#[test]
+fn test_checksums_attached_to_specs() {
+    let input = include_str!("../tests/inputs/Gemfile.lock.withchecksums");
+    let output = must_parse(input);
+    let racc = output.gem[0]
+        .specs
+        .iter()
+        .find(|spec| spec.gem_version.name == "racc")
+        .unwrap();
+    let checksum = racc.checksum.as_ref().unwrap();
+    assert_eq!(
+        checksum.algorithm,
+        crate::datatypes::ChecksumAlgorithm::SHA256
+    );
+    assert_eq!(
+        hex::encode(&checksum.value),
+        "4a7f6929691dbec8b5209a0b373bc2614882b55fc5d2e447a21aaa691303d62f"
+    );
+    assert_eq!(output.checksum_for(&racc.gem_version), Some(checksum));
+    assert!(
+        output.gem[0]
+            .specs
+            .iter()
+            .all(|spec| spec.checksum.is_some())
+    );
+}
+
+#[test]
+fn test_checksums_without_digest_not_attached() {
+    let input = include_str!("../tests/inputs/Gemfile.lock.withoutsource");
+    let output = must_parse(input);
+    assert!(
+        output
+            .gem
+            .iter()
+            .flat_map(|section| section.specs.iter())
+            .all(|spec| spec.checksum.is_none())
+    );
+}
+
+#[test]
+fn test_malformed_checksum_reports_offset() {
+    let input = "CHECKSUMS\n  racc (1.8.1) sha256=not-hex\n\nBUNDLED WITH\n   2.7.2\n";
+    let errors = crate::parse(input).unwrap_err();
+    assert_eq!(errors.others.len(), 1);
+    assert_eq!(
+        errors.others[0].char_offset.offset(),
+        input.find("not-hex").unwrap()
+    );
+}
+
+#[test]
+fn test_gem_specs_for_platform() {
+    use rv_gem_types::Platform;
+
+    let input = include_str!("../tests/inputs/Gemfile.lock.platforms");
+    let output = must_parse(input);
+    assert_eq!(
+        output.platforms,
+        vec![
+            Platform::new("arm64-darwin").unwrap(),
+            Platform::new("x86_64-linux").unwrap()
+        ]
+    );
+
+    let names = |platform: &str| -> Vec<String> {
+        let platform = Platform::new(platform).unwrap();
+        output
+            .gem_specs_for_platform(&platform)
+            .into_iter()
+            .map(|spec| format!("{} ({})", spec.gem_version.name, spec.gem_version.version))
+            .collect()
+    };
+    assert_eq!(
+        names("x86_64-linux"),
+        vec![
+            "ffi (1.17.2-x86_64-linux)",
+            "nokogiri (1.18.10-x86_64-linux)",
+            "racc (1.8.1)"
+        ]
+    );
+    assert_eq!(
+        names("arm64-darwin-23"),
+        vec![
+            "ffi (1.17.2-arm64-darwin)",
+            "nokogiri (1.18.10-arm64-darwin)",
+            "racc (1.8.1)"
+        ]
+    );
+    // No native build for this platform, so only the pure-Ruby variants remain.
+    assert_eq!(names("x86_64-darwin"), vec!["ffi (1.17.2)", "racc (1.8.1)"]);
+}
+
+#[test]
+fn test_platform_round_trip() {
+    use crate::datatypes::Platform;
+
+    for platform in [
+        "ruby",
+        "java",
+        "x86_64-linux",
+        "arm64-darwin-23",
+        "x64-mingw-ucrt",
+    ] {
+        assert_eq!(Platform::new(platform).unwrap().to_string(), platform);
+    }
+
+    let input = include_str!("../tests/inputs/Gemfile.lock.platforms");
+    let output = must_parse(input);
+    let platforms: Vec<_> = output
+        .gem
+        .iter()
+        .flat_map(|section| section.specs.iter())
+        .map(|spec| spec.platform().unwrap().to_string())
+        .collect();
+    assert!(platforms.contains(&"ruby".to_string()));
+    assert!(platforms.contains(&"x86_64-linux".to_string()));
+    assert!(platforms.contains(&"arm64-darwin".to_string()));
+}
+
+#[test]
+fn test_bundled_with() {
+    let input = include_str!("../tests/inputs/Gemfile.lock.test0");
+    assert_eq!(must_parse(input).bundled_with, Some("4.0.0.dev"));
+
+    let input = "PLATFORMS\n  ruby\n";
+    assert_eq!(must_parse(input).bundled_with, None);
+}
+
+#[test]
+fn test_invalid_bundled_with_reports_line() {
+    let input = "PLATFORMS\n  ruby\n\nBUNDLED WITH\n   2.7.2 or so\n";
+    let errors = crate::parse(input).unwrap_err();
+    assert_eq!(errors.others.len(), 1);
+    let span = errors.others[0].char_offset;
+    assert_eq!(span.offset(), input.find("2.7.2").unwrap());
+    assert_eq!(span.len(), "2.7.2 or so".len());
+}
+
+#[test]
+fn test_malformed_spec_version_reports_token() {
+    let input = "GEM\n  remote: https://rubygems.org/\n  specs:\n    gemname (not-a-version)\n\nPLATFORMS\n  ruby\n";
+    let errors = crate::parse(input).unwrap_err();
+    assert_eq!(errors.others.len(), 1);
+    let span = errors.others[0].char_offset;
+    assert_eq!(span.offset(), input.find("not-a-version").unwrap());
+    assert_eq!(span.len(), "not-a-version".len());
+}
+
+#[test]
+fn test_spans_are_byte_offsets() {
+    // The multi-byte remote shifts the byte offset of the bad version away from its char offset.
+    let input =
+        "GEM\n  remote: https://gems.example/ünïcödé/\n  specs:\n    gemname (not-a-version)\n";
+    let errors = crate::parse(input).unwrap_err();
+    let span = errors.others[0].char_offset;
+    assert_eq!(span.offset(), input.find("not-a-version").unwrap());
+    assert_eq!(span.len(), "not-a-version".len());
+}
+
+#[test]
+fn test_parse_crlf_line_endings() {
+    for input in [
+        include_str!("../tests/inputs/Gemfile.lock.feedyouremail"),
+        include_str!("../tests/inputs/Gemfile.lock.gitlab"),
+        include_str!("../tests/inputs/Gemfile.lock.withchecksums"),
+    ] {
+        let crlf = input.replace('\n', "\r\n");
+        let parsed = must_parse(&crlf);
+        assert_eq!(parsed, must_parse(input));
+
+        let texts = |input: &str| -> Vec<crate::parser::SpecText> {
+            crate::parser::parse_specs(input.as_bytes())
+                .collect::<Result<_, _>>()
+                .unwrap()
+        };
+        let (crlf_texts, lf_texts) = (texts(&crlf), texts(input));
+        assert_eq!(
+            crlf_texts
+                .iter()
+                .map(|text| text.spec())
+                .collect::<Vec<_>>(),
+            lf_texts.iter().map(|text| text.spec()).collect::<Vec<_>>()
+        );
+        assert!(
+            parsed
+                .gem
+                .iter()
+                .all(|gem| { gem.remotes.iter().all(|remote| !remote.ends_with('\r')) })
+        );
+    }
+
+    // Spans still point at the bad token, not one byte off for every `\r` before it.
+    let input =
+        "GEM\r\n  remote: https://rubygems.org/\r\n  specs:\r\n    gemname (not-a-version)\r\n";
+    let errors = crate::parse(input).unwrap_err();
+    let span = errors.others[0].char_offset;
+    assert_eq!(span.offset(), input.find("not-a-version").unwrap());
+    assert_eq!(span.len(), "not-a-version".len());
+
+    // Likewise when the specs are read one at a time.
+    let input = "GEM\r\n  specs:\r\n    rack (3.1.0)\r\n      rake (>= 1)\r\n    gemname (not-a-version)\r\n";
+    let specs: Vec<_> = crate::parser::parse_specs(input.as_bytes()).collect();
+    assert_eq!(specs.len(), 2);
+    let span = specs[1].as_ref().unwrap_err().char_offset;
+    assert_eq!(span.offset(), input.find("not-a-version").unwrap());
+    assert_eq!(span.len(), "not-a-version".len());
+}
+
+#[test]
+fn test_errors_map_to_line_and_column() {
+    use crate::LineColumn;
+
+    // The multi-byte remote mustn't throw off columns on later lines.
+    let input =
+        "GEM\n  remote: https://gems.example/ünïcödé/\n  specs:\n    gemname (not-a-version)\n";
+    let errors = crate::parse(input).unwrap_err();
+    assert_eq!(
+        errors.line_columns(),
+        vec![(
+            LineColumn {
+                line: 4,
+                column: 14
+            },
+            LineColumn {
+                line: 4,
+                column: 27
+            },
+        )]
+    );
+    assert_eq!(
+        errors.others[0].line_columns(input),
+        errors.line_columns()[0]
+    );
+    assert_eq!(errors.others[0].span(), errors.others[0].char_offset);
+
+    // On the first line, and with multi-byte characters before the error on the same line.
+    assert_eq!(
+        LineColumn::at("ünï (x)", "ünï (".len()),
+        LineColumn { line: 1, column: 6 }
+    );
+}
+
+#[test]
+fn test_find_spec() {
+    let input = include_str!("../tests/inputs/Gemfile.lock.gitlab");
+    let output = must_parse(input);
+
+    let (section, spec) = output.find_spec("rails").unwrap();
+    assert_eq!(section.remotes, vec!["https://rubygems.org/"]);
+    assert_eq!(spec.gem_version.version, "7.1.5.2");
+    assert!(spec.deps.iter().any(|dep| dep.name == "activerecord"));
+
+    // Path gems aren't from a RubyGems source.
+    assert!(output.find_spec("activerecord-gitlab").is_none());
+    assert!(output.find_spec("not-a-real-gem").is_none());
+}
+
+#[test]
+fn test_specs_covers_every_section() {
+    let input = include_str!("../tests/inputs/Gemfile.lock.gitlab");
+    let output = must_parse(input);
+
+    let expected = output.git.iter().map(|s| s.specs.len()).sum::<usize>()
+        + output.gem.iter().map(|s| s.specs.len()).sum::<usize>()
+        + output.path.iter().map(|s| s.specs.len()).sum::<usize>();
+    assert_eq!(output.specs().count(), expected);
+    assert!(
+        output
+            .specs()
+            .any(|spec| spec.gem_version.name == "activerecord-gitlab")
+    );
+    assert!(output.specs().any(|spec| spec.gem_version.name == "rails"));
+}
+
+#[test]
+fn test_serialize_round_trip() {
+    let input = include_str!("../tests/inputs/Gemfile.lock.gitlab");
+    let output = must_parse(input);
+    let serialized = output.to_string();
+    // Bundler wrote this lockfile, so serializing it should give back the same text.
+    assert_eq!(serialized.trim_end(), input.trim_end());
+    let reparsed = must_parse(&serialized);
+    assert_eq!(reparsed, output);
+    assert_eq!(reparsed.to_string(), serialized);
+}
+
+#[test]
+fn test_serialize_all_sections() {
+    for input in [
+        include_str!("../tests/inputs/Gemfile.lock.discourse"),
+        include_str!("../tests/inputs/Gemfile.lock.feedyouremail"),
+        include_str!("../tests/inputs/Gemfile.lock.withchecksums"),
+        include_str!("../tests/inputs/Gemfile.lock.withoutsource"),
+    ] {
+        let output = must_parse(input);
+        let serialized = output.to_string();
+        assert_eq!(must_parse(&serialized), output);
+    }
+}
+
+#[test]
+fn test_parse_specs_streaming() {
+    let input = include_str!("../tests/inputs/Gemfile.lock.gitlab");
+    let output = must_parse(input);
+
+    let texts: Vec<_> = crate::parser::parse_specs(input.as_bytes())
+        .collect::<Result<_, _>>()
+        .unwrap();
+    let mut streamed: Vec<_> = texts.iter().map(|text| text.spec()).collect();
+    // The batch parser attaches checksums and groups specs by kind of section.
+    let mut batch: Vec<_> = output
+        .specs()
+        .cloned()
+        .map(|spec| crate::datatypes::Spec {
+            checksum: None,
+            ..spec
+        })
+        .collect();
+    streamed.sort();
+    batch.sort();
+    assert!(!streamed.is_empty());
+    assert_eq!(streamed, batch);
+}
+
+#[test]
+fn test_parse_specs_streaming_error() {
+    let input = "GEM\n  remote: https://rubygems.org/\n  specs:\n    rack (3.1.0)\n    bad spec\n\nPLATFORMS\n  ruby\n";
+    let specs: Vec<_> = crate::parser::parse_specs(input.as_bytes()).collect();
+    assert_eq!(specs.len(), 2);
+    assert_eq!(specs[0].as_ref().unwrap().spec().gem_version.name, "rack");
+    let err = specs[1].as_ref().unwrap_err();
+    assert_eq!(err.char_offset, miette::SourceSpan::new(66.into(), 4));
+}
+
+#[test]
+fn test_diff() {
+    let old = must_parse(
+        "\
+GEM
+  remote: https://rubygems.org/
+  specs:
+    nokogiri (1.18.9-arm64-darwin)
+    rack (3.1.0)
+    rake (13.2.1)
+
+PLATFORMS
+  arm64-darwin
+",
+    );
+    let new = must_parse(
+        "\
+GEM
+  remote: https://rubygems.org/
+  specs:
+    nokogiri (1.18.9-arm64-darwin)
+    nokogiri (1.18.9-x86_64-linux)
+    puma (6.4.0)
+    rack (3.1.1)
+
+PLATFORMS
+  arm64-darwin
+  x86_64-linux
+",
+    );
+
+    let diff = crate::diff(&old, &new);
+    assert_eq!(
+        diff.to_string(),
+        "\
++ nokogiri (1.18.9-x86_64-linux)
++ puma (6.4.0)
+- rake (13.2.1)
+~ rack (3.1.0 -> 3.1.1)
+"
+    );
+    assert_eq!(
+        diff.added[0],
+        crate::diff::LockedGem {
+            name: "nokogiri",
+            platform: Some("x86_64-linux"),
+            version: "1.18.9",
+        }
+    );
+    assert!(crate::diff(&new, &new).is_empty());
+}
+
+#[test]
+fn test_validate() {
+    let input = include_str!("../tests/inputs/Gemfile.lock.platforms");
+    assert_eq!(must_parse(input).validate(), vec![]);
+
+    let input = "\
+GEM
+  remote: https://rubygems.org/
+  specs:
+    nokogiri (1.18.10-x86_64-linux)
+      racc (~> 1.4)
+    rack (3.1.0)
+    rack (3.1.0)
+    rails (8.0.0)
+      bundler (>= 1.15.0)
+
+PLATFORMS
+  arm64-darwin
+";
+    let output = must_parse(input);
+    let problems = output.validate();
+    let messages: Vec<_> = problems.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        messages,
+        [
+            "nokogiri (1.18.10-x86_64-linux) depends on racc, but no version of racc is locked",
+            "nokogiri (1.18.10-x86_64-linux) is for the x86_64-linux platform, which isn't listed in PLATFORMS",
+            "rack (3.1.0) is locked more than once",
+        ]
+    );
+    assert_eq!(
+        problems[0],
+        crate::validate::LockfileProblem::UnresolvedDependency {
+            spec: crate::datatypes::GemVersion {
+                name: "nokogiri",
+                version: "1.18.10-x86_64-linux",
+            },
+            dependency: "racc",
+        }
+    );
+}
+
 fn must_parse(input: &str) -> crate::datatypes::GemfileDotLock<'_> {
     match crate::parse(input) {
         Ok(o) => o,
@@ -81,3 +510,154 @@ fn must_parse(input: &str) -> crate::datatypes::GemfileDotLock<'_> {
         }
     }
 }
+
+#[test]
+fn test_compare_versions() {
+    use crate::datatypes::{GemVersion, compare_versions};
+    use std::cmp::Ordering;
+
+    // Prereleases come before their release, in RubyGems order.
+    assert_eq!(compare_versions("1.0.0.pre", "1.0.0"), Ordering::Less);
+    assert_eq!(
+        compare_versions("1.0.0.alpha", "1.0.0.beta"),
+        Ordering::Less
+    );
+    assert_eq!(compare_versions("1.0.0.rc1", "1.0.0.rc10"), Ordering::Less);
+    assert_eq!(compare_versions("1.0.0.rc1", "0.9.9"), Ordering::Greater);
+
+    // Segments are compared numerically, not as strings.
+    assert_eq!(compare_versions("1.10", "1.9"), Ordering::Greater);
+    assert_eq!(compare_versions("2.0.0", "10.0.0"), Ordering::Less);
+
+    // Trailing zeroes don't matter.
+    assert_eq!(compare_versions("1.0", "1.0.0"), Ordering::Equal);
+    assert_eq!(compare_versions("1", "1.0.0.0"), Ordering::Equal);
+    assert_eq!(compare_versions("1.0", "1.0.1"), Ordering::Less);
+
+    // Platforms are ignored.
+    let native = GemVersion {
+        name: "nokogiri",
+        version: "1.18.10-arm64-darwin",
+    };
+    let pure = GemVersion {
+        name: "nokogiri",
+        version: "1.18.9",
+    };
+    assert_eq!(native.cmp_version(&pure), Ordering::Greater);
+    assert_eq!(pure.cmp_version(&native), Ordering::Less);
+    assert!(native.is_newer_than("1.18.9"));
+    assert!(!native.is_newer_than("1.18.10"));
+}
+
+#[test]
+fn test_dependency_graph() {
+    use std::collections::BTreeSet;
+
+    let input = include_str!("../tests/inputs/Gemfile.lock.test0");
+    let output = must_parse(input);
+    let graph = output.dependency_graph();
+
+    assert_eq!(graph.roots().collect::<Vec<_>>(), vec!["tapioca"]);
+    assert!(graph.is_root("tapioca"));
+    assert!(!graph.is_root("spoom"));
+
+    assert_eq!(
+        graph
+            .direct_deps("sorbet-static-and-runtime")
+            .collect::<Vec<_>>(),
+        vec!["sorbet", "sorbet-runtime"]
+    );
+    assert_eq!(graph.direct_deps("thor").count(), 0);
+    assert_eq!(graph.direct_deps("not-a-real-gem").count(), 0);
+
+    // spoom -> sorbet-static-and-runtime -> sorbet -> sorbet-static
+    assert_eq!(
+        graph.transitive_deps("spoom"),
+        BTreeSet::from([
+            "erubi",
+            "prism",
+            "sorbet",
+            "sorbet-runtime",
+            "sorbet-static",
+            "sorbet-static-and-runtime",
+            "thor",
+        ])
+    );
+    // Everything is locked because of tapioca, plus bundler, which isn't locked itself.
+    let mut everything: BTreeSet<_> = output.specs().map(|spec| spec.gem_version.name).collect();
+    everything.remove("tapioca");
+    everything.insert("bundler");
+    assert_eq!(graph.transitive_deps("tapioca"), everything);
+
+    assert_eq!(
+        graph.reverse_deps("sorbet-runtime"),
+        BTreeSet::from(["rbi", "sorbet-static-and-runtime", "yard-sorbet"])
+    );
+    assert_eq!(
+        graph.reverse_deps("sorbet-static-and-runtime"),
+        BTreeSet::from(["spoom", "tapioca"])
+    );
+    assert!(graph.reverse_deps("tapioca").is_empty());
+
+    assert_eq!(graph.find_cycle(), None);
+}
+
+#[test]
+fn test_dependency_graph_cycle() {
+    let input = "GEM\n  remote: https://rubygems.org/\n  specs:\n    a (1.0)\n      b\n    b (1.0)\n      c\n    c (1.0)\n      b\n\nDEPENDENCIES\n  a\n";
+    let output = must_parse(input);
+    let graph = output.dependency_graph();
+
+    assert_eq!(graph.find_cycle(), Some(vec!["b", "c", "b"]));
+    assert!(graph.transitive_deps("b").contains("b"));
+    assert!(!graph.transitive_deps("a").contains("a"));
+}
+
+#[test]
+fn test_dependency_graph_paths_to() {
+    let input = include_str!("../tests/inputs/Gemfile.lock.test0");
+    let output = must_parse(input);
+    let graph = output.dependency_graph();
+
+    assert_eq!(graph.paths_to("tapioca"), vec![vec!["tapioca"]]);
+    assert_eq!(
+        graph.paths_to("sorbet-static"),
+        vec![
+            vec![
+                "tapioca",
+                "sorbet-static-and-runtime",
+                "sorbet",
+                "sorbet-static"
+            ],
+            vec![
+                "tapioca",
+                "spoom",
+                "sorbet-static-and-runtime",
+                "sorbet",
+                "sorbet-static"
+            ],
+        ]
+    );
+    assert!(graph.paths_to("not-a-real-gem").is_empty());
+}
+
+#[test]
+fn test_dependency_graph_paths_to_skips_unrelated_gems() {
+    use std::fmt::Write;
+
+    // Every gem in the ladder depends on both gems in the next rung, so there are 2^40 paths
+    // through it, none of which lead to `leaf`.
+    let mut input = String::from("GEM\n  remote: https://rubygems.org/\n  specs:\n");
+    input.push_str("    app (1.0)\n      a0\n      leaf\n    leaf (1.0)\n");
+    for rung in 0..40 {
+        for gem in ["a", "b"] {
+            writeln!(input, "    {gem}{rung} (1.0)").unwrap();
+            writeln!(input, "      a{}\n      b{}", rung + 1, rung + 1).unwrap();
+        }
+    }
+    input.push_str("    a40 (1.0)\n    b40 (1.0)\n\nDEPENDENCIES\n  app\n");
+    let output = must_parse(&input);
+    let graph = output.dependency_graph();
+
+    assert_eq!(graph.paths_to("leaf"), vec![vec!["app", "leaf"]]);
+}
@@ -0,0 +1,68 @@
+//! Consistency checks for lockfiles that may have been edited by hand, or merged badly.
+
+use std::collections::HashSet;
+
+use crate::datatypes::{GemVersion, GemfileDotLock};
+
+/// Something in a lockfile that Bundler would never have written.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum LockfileProblem<'i> {
+    #[error("{spec} depends on {dependency}, but no version of {dependency} is locked")]
+    UnresolvedDependency {
+        spec: GemVersion<'i>,
+        dependency: &'i str,
+    },
+    #[error("{spec} is locked more than once")]
+    DuplicateSpec { spec: GemVersion<'i> },
+    #[error("{spec} is for the {platform} platform, which isn't listed in PLATFORMS")]
+    PlatformMismatch {
+        spec: GemVersion<'i>,
+        platform: &'i str,
+    },
+}
+
+impl<'i> GemfileDotLock<'i> {
+    /// Check that the specs in this lockfile are consistent with each other,
+    /// returning every problem found.
+    pub fn validate(&self) -> Vec<LockfileProblem<'i>> {
+        let mut problems = Vec::new();
+
+        // Bundler itself is never locked as a spec, but gems can depend on it.
+        let mut locked: HashSet<&str> = HashSet::from(["bundler"]);
+        locked.extend(self.specs().map(|spec| spec.gem_version.name));
+
+        let mut seen = HashSet::new();
+        for spec in self.specs() {
+            let gem_version = &spec.gem_version;
+            if !seen.insert(gem_version) {
+                problems.push(LockfileProblem::DuplicateSpec {
+                    spec: gem_version.clone(),
+                });
+            }
+
+            for dep in &spec.deps {
+                if !locked.contains(dep.name) {
+                    problems.push(LockfileProblem::UnresolvedDependency {
+                        spec: gem_version.clone(),
+                        dependency: dep.name,
+                    });
+                }
+            }
+
+            if let Some(platform) = gem_version.platform()
+                && !self.platforms.is_empty()
+                && !self
+                    .platforms
+                    .iter()
+                    .any(|listed| spec.matches_platform(listed))
+            {
+                problems.push(LockfileProblem::PlatformMismatch {
+                    spec: gem_version.clone(),
+                    platform,
+                });
+            }
+        }
+
+        problems
+    }
+}
@@ -1,5 +1,6 @@
 pub mod engine;
 pub mod request;
+pub mod requirement;
 pub mod version;
 
 use camino::Utf8PathBuf;
@@ -23,6 +24,9 @@ pub struct Release {
 pub struct Asset {
     pub name: String,
     pub browser_download_url: String,
+    /// The asset's digest, e.g. `sha256:<hex>`, if the release lists one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub digest: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -2,7 +2,7 @@ use camino::Utf8PathBuf;
 use rv_cache::{CacheKey, CacheKeyHasher};
 use std::{fmt::Display, str::FromStr};
 
-use crate::{Ruby, engine::RubyEngine};
+use crate::{Ruby, engine::RubyEngine, version::RubyVersion};
 use serde_with::{DeserializeFromStr, SerializeDisplay};
 
 pub type VersionPart = u32;
@@ -21,6 +21,8 @@ pub struct RubyRequest {
 pub enum Source {
     DotToolVersions(Utf8PathBuf),
     DotRubyVersion(Utf8PathBuf),
+    /// The version file used outside of any project, set by `rv ruby pin --global`.
+    Global(Utf8PathBuf),
     Other,
 }
 
@@ -29,6 +31,7 @@ impl std::fmt::Debug for Source {
         match self {
             Self::DotToolVersions(arg0) => f.debug_tuple("DotToolVersions").field(arg0).finish(),
             Self::DotRubyVersion(arg0) => f.debug_tuple("DotRubyVersion").field(arg0).finish(),
+            Self::Global(arg0) => f.debug_tuple("Global").field(arg0).finish(),
             Self::Other => write!(f, "Other"),
         }
     }
@@ -74,8 +77,11 @@ impl RubyRequest {
     }
 
     pub fn satisfied_by(&self, ruby: &Ruby) -> bool {
-        let version = &ruby.version;
+        self.matches(&ruby.version)
+    }
 
+    /// Whether the version starts with every segment given in this request.
+    pub fn matches(&self, version: &RubyVersion) -> bool {
         if self.engine != version.engine {
             return false;
         }
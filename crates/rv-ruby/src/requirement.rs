@@ -0,0 +1,182 @@
+use std::{fmt::Display, str::FromStr};
+
+use rv_gem_types::{Requirement, Version, requirement::RequirementError};
+
+use crate::{
+    Ruby,
+    engine::RubyEngine,
+    request::{MatchError, RequestError, RubyRequest},
+    version::RubyVersion,
+};
+
+/// A requirement on the version of a Ruby interpreter.
+///
+/// This is either a version prefix, like `3.4` or `jruby-9.4`, or RubyGems-style constraints,
+/// like `>= 3.2, < 3.4` or `~> 3.3.0`, as found in the `RUBY VERSION` of a lockfile.
+/// Constraints always apply to CRuby.
+#[derive(Debug, Clone)]
+pub enum VersionRequirement {
+    /// Matches every version starting with the requested segments.
+    Prefix(RubyRequest),
+    /// Matches every CRuby version satisfying all the constraints.
+    Constraints(Requirement),
+}
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum VersionRequirementError {
+    #[error(transparent)]
+    Request(#[from] RequestError),
+    #[error(transparent)]
+    Requirement(#[from] RequirementError),
+}
+
+impl VersionRequirement {
+    pub fn matches(&self, version: &RubyVersion) -> bool {
+        match self {
+            Self::Prefix(request) => request.matches(version),
+            Self::Constraints(requirement) => {
+                version.engine == RubyEngine::Ruby
+                    && gem_version(version).is_some_and(|v| requirement.satisfied_by(&v))
+            }
+        }
+    }
+
+    /// Find the highest of the given rubies that satisfies this requirement.
+    pub fn find_match_in<'a>(&self, rubies: &'a [Ruby]) -> Result<&'a Ruby, MatchError> {
+        rubies
+            .iter()
+            .filter(|ruby| self.matches(&ruby.version))
+            .max_by_key(|ruby| gem_version(&ruby.version))
+            .ok_or_else(|| MatchError::NotFound(self.to_string()))
+    }
+}
+
+/// Compare ruby versions the way RubyGems does, so that prereleases sort before releases.
+fn gem_version(version: &RubyVersion) -> Option<Version> {
+    Version::new(version.number()).ok()
+}
+
+impl FromStr for VersionRequirement {
+    type Err = VersionRequirementError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let input = input.trim();
+        if input.starts_with(['<', '>', '=', '!', '~']) {
+            let requirement = Requirement::new(input.split(',').collect::<Vec<_>>())?;
+            Ok(Self::Constraints(requirement))
+        } else {
+            Ok(Self::Prefix(input.parse()?))
+        }
+    }
+}
+
+impl From<RubyRequest> for VersionRequirement {
+    fn from(request: RubyRequest) -> Self {
+        Self::Prefix(request)
+    }
+}
+
+impl From<&str> for VersionRequirement {
+    fn from(val: &str) -> Self {
+        Self::from_str(val).expect("Failed to parse string: {val}")
+    }
+}
+
+impl Display for VersionRequirement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Prefix(request) => write!(f, "{request}"),
+            Self::Constraints(requirement) => write!(f, "{requirement}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn req(input: &str) -> VersionRequirement {
+        input.parse().expect("Failed to parse requirement")
+    }
+
+    fn v(input: &str) -> RubyVersion {
+        input.parse().expect("Failed to parse version")
+    }
+
+    fn ruby(version: &str) -> Ruby {
+        Ruby {
+            key: format!("{version}-macos-aarch64"),
+            version: v(version),
+            path: format!("/opt/rubies/{version}").into(),
+            symlink: None,
+            arch: "aarch64".into(),
+            os: "macos".into(),
+            gem_root: None,
+        }
+    }
+
+    #[test]
+    fn test_prefix() {
+        assert!(req("3.4").matches(&v("3.4.1")));
+        assert!(req("3").matches(&v("3.3.4")));
+        assert!(!req("3.4").matches(&v("3.3.4")));
+        assert!(!req("3.4").matches(&v("jruby-3.4.0")));
+        assert!(req("jruby-9.4").matches(&v("jruby-9.4.8.0")));
+    }
+
+    #[test]
+    fn test_pessimistic() {
+        assert!(req("~> 3.3.0").matches(&v("3.3.0")));
+        assert!(req("~> 3.3.0").matches(&v("3.3.9")));
+        assert!(!req("~> 3.3.0").matches(&v("3.4.0")));
+        assert!(req("~> 3.3").matches(&v("3.4.1")));
+        assert!(!req("~> 3.3").matches(&v("3.2.9")));
+        assert!(!req("~> 3.3").matches(&v("jruby-3.3.0")));
+    }
+
+    #[test]
+    fn test_range() {
+        let range = req(">= 3.2, < 3.4");
+        assert!(range.matches(&v("3.2.0")));
+        assert!(range.matches(&v("3.3.9")));
+        assert!(!range.matches(&v("3.1.6")));
+        assert!(!range.matches(&v("3.4.0")));
+        assert!(range.matches(&v("3.3.0-preview1")));
+    }
+
+    #[test]
+    fn test_find_highest_match() {
+        let rubies = [
+            ruby("ruby-3.2.9"),
+            ruby("ruby-3.3.9"),
+            ruby("ruby-3.3.4"),
+            ruby("ruby-3.4.0-preview1"),
+            ruby("ruby-3.4.1"),
+        ];
+
+        let found = req(">= 3.2, < 3.4").find_match_in(&rubies).unwrap();
+        assert_eq!(found.version, v("3.3.9"));
+        let found = req("~> 3.3.0").find_match_in(&rubies).unwrap();
+        assert_eq!(found.version, v("3.3.9"));
+        let found = req("3.4").find_match_in(&rubies).unwrap();
+        assert_eq!(found.version, v("3.4.1"));
+        let found = req("> 3.3.9").find_match_in(&rubies).unwrap();
+        assert_eq!(found.version, v("3.4.1"));
+    }
+
+    #[test]
+    fn test_no_match() {
+        let rubies = [ruby("ruby-3.2.9"), ruby("ruby-3.3.4")];
+
+        let err = req("~> 3.4.0").find_match_in(&rubies).unwrap_err();
+        assert_eq!(err, MatchError::NotFound("~> 3.4.0".into()));
+        let err = req("3.1").find_match_in(&rubies).unwrap_err();
+        assert_eq!(err, MatchError::NotFound("ruby-3.1".into()));
+    }
+
+    #[test]
+    fn test_invalid_requirement() {
+        assert!(">= three".parse::<VersionRequirement>().is_err());
+        assert!("".parse::<VersionRequirement>().is_err());
+    }
+}
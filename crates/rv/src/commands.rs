@@ -1,3 +1,6 @@
 pub mod cache;
+pub mod doctor;
+pub mod lock;
 pub mod ruby;
 pub mod shell;
+pub mod why;
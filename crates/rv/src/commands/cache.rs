@@ -1,10 +1,12 @@
-use std::io;
+use std::io::{self, Cursor};
 
-use anstream::println;
+use anstream::{eprintln, println};
 use bytesize::ByteSize;
+use camino::Utf8Path;
 use clap::{Args, Subcommand};
 use owo_colors::OwoColorize;
-use rv_cache::CleanReporter;
+use rv_cache::{CacheBucket, ProgressReporter, rm_rf};
+use rv_gem_package::Package;
 
 use crate::config::Config;
 
@@ -19,22 +21,45 @@ pub enum CacheCommand {
     #[command(about = "Clear the cache")]
     Clean,
     #[command(about = "Prune all unused entries from the cache")]
-    Prune,
+    Prune {
+        /// Show what would be removed, without removing anything.
+        #[arg(long)]
+        dry_run: bool,
+    },
+    #[command(about = "Check that the cached gems aren't corrupt")]
+    Verify {
+        /// Remove corrupt gems, so they're downloaded again next time they're needed.
+        #[arg(long)]
+        fix: bool,
+    },
     #[command(about = "Show the cache directory")]
-    Dir,
+    Dir {
+        /// Show the directory of this cache bucket, instead of the cache root.
+        #[arg(long)]
+        bucket: Option<CacheBucket>,
+    },
 }
 
-pub fn cache_dir(config: &Config) -> io::Result<()> {
-    println!("{}", config.cache.root().as_str().cyan());
+pub fn cache_dir(config: &Config, bucket: Option<CacheBucket>) -> io::Result<()> {
+    let cache = config.cache.clone().init()?;
+    if cache.is_temporary() {
+        eprintln!(
+            "{}",
+            "Note: caching is disabled, so this temporary directory will be deleted when rv exits."
+                .yellow()
+        );
+    }
+    let dir = match bucket {
+        Some(bucket) => cache.bucket(bucket),
+        None => cache.root().to_owned(),
+    };
+    println!("{}", dir.as_str().cyan());
     Ok(())
 }
+
 pub fn cache_clean(config: &Config) -> io::Result<()> {
-    struct Reporter {}
-    impl CleanReporter for Reporter {
-        fn on_clean(&self) {}
-        fn on_complete(&self) {}
-    }
-    let removal = config.cache.clear(Box::new(Reporter {}))?;
+    let _lock = config.cache.lock_existing()?;
+    let removal = config.cache.clear(Box::new(ProgressReporter::new()))?;
     let num_bytes_cleaned = ByteSize::b(removal.bytes).display().iec_short();
     println!(
         "Removed {} directories, totalling {}",
@@ -44,13 +69,56 @@ pub fn cache_clean(config: &Config) -> io::Result<()> {
     Ok(())
 }
 
-pub fn cache_prune(config: &Config) -> io::Result<()> {
-    let removal = config.cache.prune()?;
+pub fn cache_prune(config: &Config, dry_run: bool) -> io::Result<()> {
+    let removal = if dry_run {
+        config.cache.prune_dry_run()?
+    } else {
+        let _lock = config.cache.lock_existing()?;
+        config.cache.prune(Box::new(ProgressReporter::new()))?
+    };
     let num_bytes_cleaned = ByteSize::b(removal.bytes).display().iec_short();
     println!(
-        "Removed {} directories, totalling {}",
+        "{} {} directories, totalling {}",
+        if dry_run { "Would remove" } else { "Removed" },
         removal.dirs.cyan(),
         num_bytes_cleaned.cyan()
     );
     Ok(())
 }
+
+pub fn cache_verify(config: &Config, fix: bool) -> io::Result<()> {
+    let _lock = if fix {
+        config.cache.lock_existing()?
+    } else {
+        None
+    };
+    let entries = config.cache.entries(CacheBucket::Gem)?;
+    let mut corrupt = 0;
+    for entry in &entries {
+        let Err(err) = verify_gem(entry.path()) else {
+            continue;
+        };
+        corrupt += 1;
+        println!("{} {}: {err}", "Corrupt".red(), entry.path().cyan());
+        if fix {
+            rm_rf(entry.path())?;
+        }
+    }
+
+    let summary = format!("Verified {} cached gems, {corrupt} corrupt", entries.len());
+    if fix && corrupt > 0 {
+        println!("{summary}, all removed");
+    } else {
+        println!("{summary}");
+    }
+    Ok(())
+}
+
+/// Check that a cached gem has a readable gemspec, and that its checksums match.
+fn verify_gem(path: &Utf8Path) -> Result<(), Box<dyn std::error::Error>> {
+    let bytes = fs_err::read(path)?;
+    let mut package = Package::from_source(Cursor::new(bytes))?;
+    package.spec()?;
+    package.verify()?;
+    Ok(())
+}
@@ -0,0 +1,104 @@
+use anstream::println;
+use owo_colors::OwoColorize;
+use rv_ruby::{
+    Ruby,
+    request::{RubyRequest, Source},
+};
+
+use crate::config::Config;
+
+#[derive(Debug, thiserror::Error, miette::Diagnostic)]
+pub enum Error {
+    #[error("{failed} of {total} checks failed")]
+    ChecksFailed { failed: usize, total: usize },
+}
+
+type Result<T> = miette::Result<T, Error>;
+
+/// The outcome of one of the doctor's checks.
+struct Check {
+    passed: bool,
+    message: String,
+}
+
+impl Check {
+    fn pass(message: String) -> Self {
+        Self {
+            passed: true,
+            message,
+        }
+    }
+
+    fn fail(message: String) -> Self {
+        Self {
+            passed: false,
+            message,
+        }
+    }
+}
+
+pub fn doctor(config: &Config) -> Result<()> {
+    let mut checks = vec![check_cache(config)];
+    checks.extend(config.ruby_install_paths().into_iter().map(|path| {
+        match Ruby::from_dir(path.clone()) {
+            Ok(ruby) => Check::pass(format!("{} at {}", ruby.version, path)),
+            Err(err) => Check::fail(format!("{path} is broken: {err}")),
+        }
+    }));
+    checks.push(check_requested_ruby(config));
+
+    for check in &checks {
+        if check.passed {
+            println!("{} {}", "✓".green(), check.message);
+        } else {
+            println!("{} {}", "✗".red(), check.message);
+        }
+    }
+
+    let failed = checks.iter().filter(|check| !check.passed).count();
+    if failed > 0 {
+        return Err(Error::ChecksFailed {
+            failed,
+            total: checks.len(),
+        });
+    }
+    Ok(())
+}
+
+/// Can we write to the cache directory?
+fn check_cache(config: &Config) -> Check {
+    if config.cache.is_temporary() {
+        return Check::pass("Caching is disabled".to_string());
+    }
+    let root = config.cache.root();
+    let probe = root.join(".rv-doctor");
+    let result = config
+        .cache
+        .clone()
+        .init()
+        .and_then(|_| fs_err::write(&probe, b""))
+        .and_then(|_| fs_err::remove_file(&probe));
+    match result {
+        Ok(()) => Check::pass(format!("Cache directory {root} is writable")),
+        Err(err) => Check::fail(format!("Cache directory {root} is not writable: {err}")),
+    }
+}
+
+/// Is the Ruby requested for the current directory installed?
+fn check_requested_ruby(config: &Config) -> Check {
+    let (request, from) = match &config.requested_ruby {
+        Some((
+            request,
+            Source::DotRubyVersion(path) | Source::DotToolVersions(path) | Source::Global(path),
+        )) => (request.clone(), format!(" (from {path})")),
+        Some((request, Source::Other)) => (request.clone(), String::new()),
+        None => (RubyRequest::default().into(), String::new()),
+    };
+    match config.matching_ruby(&request) {
+        Some(ruby) => Check::pass(format!(
+            "{request}{from} resolves to {}",
+            ruby.executable_path()
+        )),
+        None => Check::fail(format!("{request}{from} is not installed")),
+    }
+}
@@ -0,0 +1,53 @@
+use anstream::{print, println};
+use camino::{Utf8Path, Utf8PathBuf};
+use clap::{Args, Subcommand};
+
+#[derive(Args)]
+pub struct LockCommandArgs {
+    #[command(subcommand)]
+    pub command: LockCommand,
+}
+
+#[derive(Subcommand)]
+pub enum LockCommand {
+    #[command(about = "Show which gems were added, removed or changed between two lockfiles")]
+    Diff {
+        /// The old Gemfile.lock
+        old: Utf8PathBuf,
+
+        /// The new Gemfile.lock
+        new: Utf8PathBuf,
+
+        /// Output the differences as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Debug, thiserror::Error, miette::Diagnostic)]
+pub enum Error {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    ParseError(#[from] rv_lockfile::ParseErrors),
+    #[error(transparent)]
+    JsonError(#[from] serde_json::Error),
+}
+
+type Result<T> = miette::Result<T, Error>;
+
+pub fn lock_diff(old: &Utf8Path, new: &Utf8Path, json: bool) -> Result<()> {
+    let old = fs_err::read_to_string(old)?;
+    let new = fs_err::read_to_string(new)?;
+    let old = rv_lockfile::parse(&old)?;
+    let new = rv_lockfile::parse(&new)?;
+
+    let diff = rv_lockfile::diff(&old, &new);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&diff)?);
+    } else {
+        print!("{diff}");
+    }
+    Ok(())
+}
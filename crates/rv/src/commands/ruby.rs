@@ -26,6 +26,10 @@ pub enum RubyCommand {
         #[arg(long, value_enum, default_value = "text")]
         format: OutputFormat,
 
+        /// Output the Ruby list as JSON, same as `--format json`
+        #[arg(long, conflicts_with = "format")]
+        json: bool,
+
         /// Show only installed Ruby versions
         #[arg(long)]
         installed_only: bool,
@@ -35,15 +39,27 @@ pub enum RubyCommand {
     Pin {
         /// The Ruby version to pin
         version_request: Option<String>,
+
+        /// Output the pinned Ruby version as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Show or set the Ruby version used outside of any project
+        #[arg(long)]
+        global: bool,
     },
 
     #[command(about = "Show the Ruby installation directory")]
     Dir,
 
-    #[command(about = "Search for a Ruby installation")]
+    #[command(about = "Search for a Ruby installation", visible_alias = "which")]
     Find {
         /// Ruby version to find
         request: Option<RubyRequest>,
+
+        /// Ruby version to find, same as passing it as an argument
+        #[arg(long, conflicts_with = "request")]
+        version: Option<RubyRequest>,
     },
 
     #[command(about = "Install a Ruby version")]
@@ -58,12 +74,20 @@ pub enum RubyCommand {
         /// Path to a local ruby tarball
         #[arg(long, value_name = "TARBALL_PATH")]
         tarball_path: Option<String>,
+
+        /// Reinstall the Ruby version even if it is already installed
+        #[arg(long)]
+        force: bool,
     },
 
     #[command(about = "Uninstall a Ruby version")]
     Uninstall {
         /// Ruby version to uninstall
         version: RubyRequest,
+
+        /// Uninstall the Ruby version even if it is the pinned one
+        #[arg(long)]
+        force: bool,
     },
 
     #[cfg(unix)]
@@ -1,8 +1,7 @@
-use std::borrow::Cow;
-
 use anstream::println;
 use owo_colors::OwoColorize;
 use rv_ruby::request::RubyRequest;
+use rv_ruby::requirement::VersionRequirement;
 
 use crate::config::Config;
 
@@ -18,9 +17,9 @@ type Result<T> = miette::Result<T, Error>;
 
 pub fn find(config: &Config, request: &Option<RubyRequest>) -> Result<()> {
     let request = if let Some(request) = request {
-        Cow::Borrowed(request)
+        VersionRequirement::from(request.clone())
     } else {
-        Cow::Owned(config.ruby_request()?)
+        config.ruby_request()?
     };
     if let Some(ruby) = config.matching_ruby(&request) {
         println!("{}", ruby.executable_path().cyan());
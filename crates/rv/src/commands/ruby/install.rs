@@ -1,13 +1,16 @@
 use anstream::println;
-use camino::{Utf8Path, Utf8PathBuf};
+use camino::{Utf8Component, Utf8Path, Utf8PathBuf};
 use core::panic;
 use current_platform::CURRENT_PLATFORM;
 use futures_util::StreamExt;
 use owo_colors::OwoColorize;
 use std::path::PathBuf;
 use tokio::io::AsyncWriteExt;
+use tracing::debug;
 
+use rv_ruby::Asset;
 use rv_ruby::request::RubyRequest;
+use sha2::{Digest, Sha256};
 
 use crate::config::Config;
 
@@ -31,6 +34,14 @@ pub enum Error {
     GetLatestReleaseFailed { error: super::list::Error },
     #[error("Failed to unpack tarball path {0}")]
     InvalidTarballPath(PathBuf),
+    #[error(
+        "Checksum of the Ruby tarball downloaded from {url} didn't match: expected {expected}, got {actual}"
+    )]
+    ChecksumMismatch {
+        url: String,
+        expected: String,
+        actual: String,
+    },
     #[error("rv does not (yet) support your platform ({0}). Sorry :(")]
     UnsupportedPlatform(&'static str),
 }
@@ -42,6 +53,7 @@ pub async fn install(
     install_dir: Option<String>,
     requested: RubyRequest,
     tarball_path: Option<String>,
+    force: bool,
 ) -> Result<()> {
     let install_dir = match install_dir {
         Some(dir) => Utf8PathBuf::from(dir),
@@ -51,6 +63,18 @@ pub async fn install(
         },
     };
 
+    let ruby_dir = install_dir.join(format!("ruby-{}", requested.number()));
+    // With `--force`, the old install is only removed once the new one has been extracted and
+    // is ready to take its place, so a failed reinstall doesn't lose a working Ruby.
+    if ruby_dir.exists() && !force {
+        println!(
+            "Ruby version {} is already installed at {}, use --force to reinstall it",
+            requested.to_string().cyan(),
+            ruby_dir.cyan()
+        );
+        return Ok(());
+    }
+
     match tarball_path {
         Some(tarball_path) => {
             extract_local_ruby_tarball(tarball_path, &install_dir, &requested.number()).await?
@@ -77,7 +101,8 @@ async fn download_and_extract_remote_tarball(
         Err(Error::IncompleteVersion(requested.clone()))?;
     }
 
-    let url = ruby_url(&requested.to_string())?;
+    let asset = ruby_asset(config, &requested.to_string()).await?;
+    let url = asset.browser_download_url;
     let tarball_path = tarball_path(config, &url);
 
     let new_dir = tarball_path.parent().unwrap();
@@ -85,18 +110,42 @@ async fn download_and_extract_remote_tarball(
         std::fs::create_dir_all(new_dir)?;
     }
 
-    if valid_tarball_exists(&tarball_path) {
-        println!(
-            "Tarball {} already exists, skipping download.",
-            tarball_path.cyan()
-        );
-    } else {
-        download_ruby_tarball(config, &url, &tarball_path).await?;
-    }
+    loop {
+        if valid_tarball_exists(&tarball_path) {
+            println!(
+                "Tarball {} already exists, skipping download.",
+                tarball_path.cyan()
+            );
+        } else {
+            download_ruby_tarball(config, &url, &tarball_path).await?;
+        }
 
-    extract_ruby_tarball(&tarball_path, install_dir, &requested.number())?;
+        // Keep `rv cache clean` and `rv cache prune` from removing the tarball while it's
+        // verified and extracted. The download itself doesn't need the lock, since it's only
+        // renamed into place once it's complete.
+        let _lock = lock_cache(&config.cache).await?;
+        if !valid_tarball_exists(&tarball_path) {
+            debug!("Tarball {tarball_path} was removed from the cache, downloading it again");
+            continue;
+        }
 
-    Ok(())
+        if let Some(digest) = &asset.digest {
+            verify_tarball(&url, &tarball_path, digest)?;
+        }
+
+        extract_ruby_tarball(&tarball_path, install_dir, &requested.number())?;
+
+        return Ok(());
+    }
+}
+
+/// Wait for the cache lock on a blocking thread, so a long wait doesn't hold up the runtime.
+async fn lock_cache(cache: &rv_cache::Cache) -> Result<rv_cache::CacheLock> {
+    let cache = cache.clone();
+    let lock = tokio::task::spawn_blocking(move || cache.lock())
+        .await
+        .map_err(std::io::Error::other)??;
+    Ok(lock)
 }
 
 // extract a local ruby tarball
@@ -124,7 +173,10 @@ fn valid_tarball_exists(path: &Utf8Path) -> bool {
     true
 }
 
-fn ruby_url(version: &str) -> Result<String> {
+/// The asset for the requested Ruby's tarball on this platform. It's looked up in the assets of
+/// the latest release, falling back to the release's usual download URL, without a digest, if
+/// the list of assets can't be fetched or doesn't include it.
+async fn ruby_asset(config: &Config, version: &str) -> Result<Asset> {
     let version = version.strip_prefix("ruby-").unwrap();
     let arch = match CURRENT_PLATFORM {
         "aarch64-apple-darwin" => "arm64_sonoma",
@@ -133,14 +185,50 @@ fn ruby_url(version: &str) -> Result<String> {
         "aarch64-unknown-linux-gnu" => "arm64_linux",
         other => return Err(Error::UnsupportedPlatform(other)),
     };
+    let asset_name = format!("ruby-{version}.{arch}.tar.gz");
+
+    match super::list::fetch_available_rubies(&config.cache).await {
+        Ok(release) => {
+            if let Some(asset) = release.assets.into_iter().find(|a| a.name == asset_name) {
+                return Ok(asset);
+            }
+            debug!("Release {} has no asset {asset_name}", release.name);
+        }
+        Err(error) => debug!("Could not fetch the latest Ruby release: {error}"),
+    }
 
     let download_base = std::env::var("RV_RELEASES_URL")
         .unwrap_or("https://github.com/spinel-coop/rv-ruby/releases".to_owned());
 
-    Ok(format!(
-        "{}/latest/download/ruby-{version}.{arch}.tar.gz",
-        download_base
-    ))
+    Ok(Asset {
+        browser_download_url: format!("{download_base}/latest/download/{asset_name}"),
+        name: asset_name,
+        digest: None,
+    })
+}
+
+/// Check the tarball at `path` against the release's `digest` for it, e.g. `sha256:<hex>`.
+/// If it doesn't match, the tarball is deleted, so the next install downloads it again.
+fn verify_tarball(url: &str, path: &Utf8Path, digest: &str) -> Result<()> {
+    let Some(expected) = digest.strip_prefix("sha256:") else {
+        debug!("Not verifying {path}, since its digest {digest} isn't SHA-256");
+        return Ok(());
+    };
+
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut std::fs::File::open(path)?, &mut hasher)?;
+    let actual = format!("{:x}", hasher.finalize());
+    if !actual.eq_ignore_ascii_case(expected) {
+        std::fs::remove_file(path)?;
+        return Err(Error::ChecksumMismatch {
+            url: url.to_owned(),
+            expected: expected.to_owned(),
+            actual,
+        });
+    }
+
+    debug!("Verified {path} has SHA-256 {actual}");
+    Ok(())
 }
 
 fn tarball_path(config: &Config, url: impl AsRef<str>) -> Utf8PathBuf {
@@ -212,6 +300,9 @@ async fn download_ruby_tarball(
     Ok(())
 }
 
+/// Extract the tarball into a temporary directory next to the rubies, then move what it contained
+/// into place. If extraction fails partway through, or rv is interrupted, no half-extracted Ruby
+/// is left where it would be mistaken for an installed one.
 fn extract_ruby_tarball(
     tarball_path: &Utf8Path,
     rubies_dir: &Utf8Path,
@@ -220,11 +311,28 @@ fn extract_ruby_tarball(
     if !rubies_dir.exists() {
         std::fs::create_dir_all(rubies_dir)?;
     }
-    let tarball = std::fs::File::open(tarball_path)?;
-    let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(tarball));
-    for e in archive.entries()? {
-        let mut entry = e?;
-        let entry_path = entry.path()?;
+    let temp_dir = tempfile::Builder::new()
+        .prefix(".rv-install-")
+        .tempdir_in(rubies_dir)?;
+    let temp_path = Utf8Path::from_path(temp_dir.path())
+        .ok_or_else(|| Error::InvalidTarballPath(temp_dir.path().to_path_buf()))?;
+    unpack_ruby_tarball(tarball_path, temp_path, version)?;
+
+    for entry in temp_path.read_dir_utf8()? {
+        let entry = entry?;
+        let dst = rubies_dir.join(entry.file_name());
+        if std::fs::symlink_metadata(&dst).is_ok() {
+            rv_cache::rm_rf(&dst)?;
+        }
+        std::fs::rename(entry.path(), &dst)?;
+    }
+
+    Ok(())
+}
+
+fn unpack_ruby_tarball(tarball_path: &Utf8Path, dir: &Utf8Path, version: &str) -> Result<()> {
+    // Paths in the tarball start with `rv-ruby@<version>/<version>`, which becomes `ruby-<version>`.
+    let rename = |entry_path: &std::path::Path| -> Result<Utf8PathBuf> {
         let path = entry_path
             .to_str()
             .ok_or_else(|| Error::InvalidTarballPath(entry_path.to_path_buf()))?
@@ -233,9 +341,113 @@ fn extract_ruby_tarball(
                 &format!("ruby-{version}"),
             )
             .replace('@', "-");
-        let dst = rubies_dir.join(path);
+        Ok(Utf8PathBuf::from(path))
+    };
+
+    let tarball = std::fs::File::open(tarball_path)?;
+    let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(tarball));
+    let root = dir.canonicalize_utf8()?;
+    for e in archive.entries()? {
+        let mut entry = e?;
+        let path = rename(&entry.path()?)?;
+
+        // Don't let a malicious tarball write anywhere outside the extraction dir, either
+        // directly or through symlinks unpacked from earlier entries.
+        if !stays_within(&path) {
+            return Err(Error::InvalidTarballPath(path.into()));
+        }
+        let entry_type = entry.header().entry_type();
+        if entry_type.is_dir() {
+            let dst = create_dir_within(&root, &path)?;
+            entry.unpack(dst)?;
+            continue;
+        }
+        let Some(file_name) = path.file_name() else {
+            continue;
+        };
+        let parent = create_dir_within(&root, path.parent().unwrap_or(Utf8Path::new("")))?;
+        let dst = parent.join(file_name);
+
+        if entry_type.is_symlink() {
+            let escapes = entry.link_name()?.is_none_or(|target| {
+                Utf8Path::from_path(&target).is_none_or(|target| {
+                    let parent = parent.strip_prefix(&root).unwrap_or(Utf8Path::new(""));
+                    !stays_within(&parent.join(target))
+                })
+            });
+            if escapes {
+                return Err(Error::InvalidTarballPath(path.into()));
+            }
+        }
+
+        if entry_type.is_hard_link() {
+            // Hard link targets are relative to the root of the tarball. `Entry::unpack` would
+            // take them as they are, so link them up ourselves, inside the extraction dir.
+            let target = entry
+                .link_name()?
+                .ok_or_else(|| Error::InvalidTarballPath(path.clone().into()))?;
+            let target = rename(&target)?;
+            let invalid_target = || Error::InvalidTarballPath(target.as_std_path().into());
+            if !stays_within(&target) {
+                return Err(invalid_target());
+            }
+            let target_name = target.file_name().ok_or_else(invalid_target)?;
+            let target_parent = root
+                .join(target.parent().unwrap_or(Utf8Path::new("")))
+                .canonicalize_utf8()?;
+            if !target_parent.starts_with(&root) {
+                return Err(invalid_target());
+            }
+            std::fs::hard_link(target_parent.join(target_name), dst)?;
+            continue;
+        }
         entry.unpack(dst)?;
     }
 
     Ok(())
 }
+
+/// Create `path` as a directory under `root` one component at a time, returning its real path.
+/// Fails if a symlink unpacked from an earlier entry would lead it outside of `root`, without
+/// creating anything out there.
+fn create_dir_within(root: &Utf8Path, path: &Utf8Path) -> Result<Utf8PathBuf> {
+    let mut dir = root.to_path_buf();
+    for component in path.components() {
+        match component {
+            Utf8Component::Normal(name) => {
+                dir.push(name);
+                match std::fs::create_dir(&dir) {
+                    Err(err) if err.kind() != std::io::ErrorKind::AlreadyExists => {
+                        return Err(err.into());
+                    }
+                    _ => {}
+                }
+                dir = dir.canonicalize_utf8()?;
+            }
+            Utf8Component::ParentDir => {
+                dir.pop();
+            }
+            Utf8Component::CurDir | Utf8Component::RootDir | Utf8Component::Prefix(_) => {}
+        }
+        if !dir.starts_with(root) {
+            return Err(Error::InvalidTarballPath(path.as_std_path().into()));
+        }
+    }
+    Ok(dir)
+}
+
+fn stays_within(path: &Utf8Path) -> bool {
+    let mut depth = 0usize;
+    for component in path.components() {
+        match component {
+            Utf8Component::Normal(_) => depth += 1,
+            Utf8Component::CurDir => {}
+            Utf8Component::ParentDir => match depth.checked_sub(1) {
+                Some(parent) => depth = parent,
+                None => return false,
+            },
+            Utf8Component::RootDir | Utf8Component::Prefix(_) => return false,
+        }
+    }
+    true
+}
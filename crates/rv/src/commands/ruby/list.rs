@@ -550,6 +550,7 @@ mod tests {
                     assets: vec![Asset {
                         name: "ruby-3.3.0.arm64_sonoma.tar.gz".to_owned(),
                         browser_download_url: u("3.3.0"),
+                        digest: None,
                     }],
                 },
                 installed_rubies: Vec::new(),
@@ -585,6 +586,7 @@ mod tests {
                     assets: vec![Asset {
                         name: "ruby-3.4.0.arm64_sonoma.tar.gz".to_owned(),
                         browser_download_url: u("3.4.0"),
+                        digest: None,
                     }],
                 },
                 installed_rubies: vec![ruby("ruby-3.3.0")],
@@ -613,6 +615,7 @@ mod tests {
                     assets: vec![Asset {
                         name: "ruby-3.4.0.arm64_sonoma.tar.gz".to_owned(),
                         browser_download_url: u("3.4.0"),
+                        digest: None,
                     }],
                 },
                 installed_rubies: vec![ruby("ruby-3.4.1")],
@@ -640,10 +643,12 @@ mod tests {
                         Asset {
                             name: "ruby-3.4.0.arm64_sonoma.tar.gz".to_owned(),
                             browser_download_url: u("3.4.0"),
+                            digest: None,
                         },
                         Asset {
                             name: "ruby-3.4.1.arm64_sonoma.tar.gz".to_owned(),
                             browser_download_url: u("3.4.1"),
+                            digest: None,
                         },
                     ],
                 },
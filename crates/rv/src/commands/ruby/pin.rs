@@ -1,48 +1,127 @@
 use std::borrow::Cow;
 
 use anstream::println;
-use camino::Utf8PathBuf;
+use camino::{Utf8Path, Utf8PathBuf};
 use miette::Diagnostic;
 use owo_colors::OwoColorize;
 use rv_ruby::request::Source;
+use rv_ruby::requirement::VersionRequirement;
+use serde::Serialize;
+use tracing::warn;
 
+use crate::commands::ruby::list::OutputFormat;
 use crate::config::Config;
 
 #[derive(Debug, thiserror::Error, Diagnostic)]
 pub enum Error {
     #[error("No Ruby version request found in {}", path.cyan())]
     NoRubyRequest { path: Utf8PathBuf },
+    #[error("No global Ruby version is pinned, set one with `rv ruby pin --global <version>`")]
+    NoGlobalRubyRequest,
     #[error(transparent)]
     IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    SerdeJsonError(#[from] serde_json::Error),
 }
 
 type Result<T> = miette::Result<T, Error>;
 
-pub fn pin(config: &Config, version: Option<String>) -> Result<()> {
+/// What `rv ruby pin --json` prints.
+#[derive(Serialize)]
+struct JsonPin<'a> {
+    /// The directory the Ruby version is pinned for.
+    path: &'a str,
+    version: &'a str,
+    /// The file the version was written to, if it was just pinned.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    wrote: Option<&'a str>,
+}
+
+pub fn pin(config: &Config, version: Option<String>, format: OutputFormat) -> Result<()> {
     match version {
-        None => show_pinned_ruby(config),
-        Some(version) => set_pinned_ruby(config, version),
+        None => show_pinned_ruby(config, format),
+        Some(version) => set_pinned_ruby(config, version, format),
+    }
+}
+
+/// Show or set the Ruby version used outside of any project.
+pub fn pin_global(config: &Config, version: Option<String>, format: OutputFormat) -> Result<()> {
+    let path = crate::config::global_ruby_version_path(&config.root);
+    match version {
+        None => {
+            let version = match std::fs::read_to_string(&path) {
+                Ok(version) => version,
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                    return Err(Error::NoGlobalRubyRequest);
+                }
+                Err(error) => return Err(error.into()),
+            };
+            print_pin(&path, version.trim(), false, format)
+        }
+        Some(version) => {
+            warn_if_not_installed(config, &version);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&path, format!("{version}\n"))?;
+            print_pin(&path, &version, true, format)
+        }
+    }
+}
+
+/// Print that the Ruby `version` is pinned in `file`, which was just `wrote` or already there.
+fn print_pin(file: &Utf8Path, version: &str, wrote: bool, format: OutputFormat) -> Result<()> {
+    match format {
+        OutputFormat::Text if wrote => {
+            println!("{0} pinned to Ruby {1}", file.cyan(), version.cyan())
+        }
+        OutputFormat::Text => println!("{0} is pinned to Ruby {1}", file.cyan(), version.cyan()),
+        OutputFormat::Json => {
+            let output = JsonPin {
+                path: file.parent().unwrap_or(file).as_str(),
+                version,
+                wrote: wrote.then(|| file.file_name()).flatten(),
+            };
+            println!("{}", serde_json::to_string(&output)?);
+        }
+    }
+    Ok(())
+}
+
+/// Pinning a Ruby that isn't installed yet is fine, but probably not what was intended.
+fn warn_if_not_installed(config: &Config, version: &str) {
+    match version.parse::<VersionRequirement>() {
+        Ok(request) if config.matching_ruby(&request).is_none() => match request {
+            VersionRequirement::Prefix(_) => warn!(
+                "Ruby {version} is not installed, install it with `rv ruby install {version}`"
+            ),
+            VersionRequirement::Constraints(_) => warn!("No installed Ruby satisfies {version}"),
+        },
+        Ok(_) => {}
+        Err(error) => warn!("{version} doesn't look like a Ruby version: {error}"),
     }
 }
 
-fn set_pinned_ruby(config: &Config, version: String) -> Result<()> {
+fn set_pinned_ruby(config: &Config, version: String, format: OutputFormat) -> Result<()> {
+    warn_if_not_installed(config, &version);
+
     let project_dir: Cow<Utf8PathBuf> = match config.requested_ruby {
         Some((_, Source::DotToolVersions(ref path))) => {
             let versions = std::fs::read_to_string(path)?;
             let mut new_versions = String::new();
             let mut wrote_ruby = false;
             for line in versions.lines() {
-                if line.starts_with("ruby ") {
+                if !wrote_ruby && line.split_whitespace().next() == Some("ruby") {
                     new_versions.push_str(&format!("ruby {version}"));
                     wrote_ruby = true;
                 } else {
                     new_versions.push_str(line);
                 }
-                if !wrote_ruby {
-                    new_versions.push_str(&format!("ruby {version}"));
-                }
                 new_versions.push('\n');
             }
+            if !wrote_ruby {
+                new_versions.push_str(&format!("ruby {version}\n"));
+            }
 
             std::fs::write(path, new_versions)?;
             Cow::Borrowed(path)
@@ -51,19 +130,18 @@ fn set_pinned_ruby(config: &Config, version: String) -> Result<()> {
             std::fs::write(path, format!("{version}\n"))?;
             Cow::Borrowed(path)
         }
-        Some((_, Source::Other)) | None => {
+        // A project pin takes precedence over the global one, so it doesn't replace it.
+        Some((_, Source::Global(_) | Source::Other)) | None => {
             let path = config.current_dir.join(".ruby-version");
             std::fs::write(&path, format!("{version}\n"))?;
             Cow::Owned(path)
         }
     };
 
-    println!("{0} pinned to Ruby {1}", project_dir.cyan(), version.cyan());
-
-    Ok(())
+    print_pin(&project_dir, &version, true, format)
 }
 
-fn show_pinned_ruby(config: &Config) -> Result<()> {
+fn show_pinned_ruby(config: &Config, format: OutputFormat) -> Result<()> {
     let Some((ruby, source)) = &config.requested_ruby else {
         return Err(Error::NoRubyRequest {
             path: config.current_dir.clone(),
@@ -73,18 +151,14 @@ fn show_pinned_ruby(config: &Config) -> Result<()> {
     let dir: Cow<Utf8PathBuf> = match source {
         Source::DotToolVersions(path) => Cow::Borrowed(path),
         Source::DotRubyVersion(ruby_version_path) => Cow::Borrowed(ruby_version_path),
+        Source::Global(path) => Cow::Borrowed(path),
         Source::Other => {
             let ruby_version_path = config.current_dir.join(".ruby-version");
             Cow::Owned(ruby_version_path)
         }
     };
 
-    println!(
-        "{0} is pinned to Ruby {1}",
-        dir.as_ref().cyan(),
-        ruby.cyan()
-    );
-    Ok(())
+    print_pin(&dir, &ruby.to_string(), false, format)
 }
 
 #[cfg(test)]
@@ -118,7 +192,7 @@ mod tests {
     fn test_pin_runs_with_no_version() {
         let mut config = test_config().unwrap();
         config.requested_ruby = None;
-        pin(&config, None).expect_err("No Ruby request found");
+        pin(&config, None, OutputFormat::Text).expect_err("No Ruby request found");
     }
 
     #[test]
@@ -127,17 +201,17 @@ mod tests {
 
         let ruby_version_file = config.current_dir.join(".ruby-version");
         config.requested_ruby = Some(("3.2.0".into(), Source::DotRubyVersion(ruby_version_file)));
-        pin(&config, None).unwrap();
+        pin(&config, None, OutputFormat::Text).unwrap();
     }
 
     #[test]
     fn test_pin_runs_with_tool_versions() {
         let mut config = test_config().unwrap();
 
-        pin(&config, None).unwrap();
+        pin(&config, None, OutputFormat::Text).unwrap();
         let version_file = config.current_dir.join(".tool-versions");
         config.requested_ruby = Some(("3.2.0".into(), Source::DotToolVersions(version_file)));
-        pin(&config, None).unwrap();
+        pin(&config, None, OutputFormat::Text).unwrap();
     }
 
     #[test]
@@ -146,7 +220,7 @@ mod tests {
         let version = "3.2.0".to_string();
 
         // Should not panic - basic smoke test
-        pin(&config, Some(version.clone())).unwrap();
+        pin(&config, Some(version.clone()), OutputFormat::Text).unwrap();
 
         // Verify the file was created
         let ruby_version_path = config.current_dir.join(".ruby-version");
@@ -162,10 +236,10 @@ mod tests {
         let second_version = "3.2.0".to_string();
 
         // Pin first version
-        pin(&config, Some(first_version)).unwrap();
+        pin(&config, Some(first_version), OutputFormat::Text).unwrap();
 
         // Pin second version (should overwrite)
-        pin(&config, Some(second_version.clone())).unwrap();
+        pin(&config, Some(second_version.clone()), OutputFormat::Text).unwrap();
 
         // Verify the file contains the second version
         let ruby_version_path = config.current_dir.join(".ruby-version");
@@ -185,19 +259,36 @@ mod tests {
         std::fs::write(&version_file, "ruby 3.0.0").unwrap();
 
         // Pin version (should overwrite)
-        pin(&config, Some("3.4.0".to_string())).unwrap();
+        pin(&config, Some("3.4.0".to_string()), OutputFormat::Text).unwrap();
 
         // Verify the file contains the second version
         let content = std::fs::read_to_string(&version_file).unwrap();
         assert_eq!(content, "ruby 3.4.0\n");
     }
 
+    #[test]
+    fn test_pin_ruby_keeps_other_tool_versions() {
+        let mut config = test_config().unwrap();
+        let version_file = config.current_dir.join(".tool-versions");
+        config.requested_ruby = Some((
+            "3.2.0".into(),
+            Source::DotToolVersions(version_file.clone()),
+        ));
+
+        std::fs::write(&version_file, "nodejs 20.1.0\nruby 3.0.0\npython 3.12.0\n").unwrap();
+
+        pin(&config, Some("3.4.0".to_string()), OutputFormat::Text).unwrap();
+
+        let content = std::fs::read_to_string(&version_file).unwrap();
+        assert_eq!(content, "nodejs 20.1.0\nruby 3.4.0\npython 3.12.0\n");
+    }
+
     #[test]
     fn test_pin_ruby_with_prerelease_version() {
         let config = test_config().unwrap();
         let version = "3.3.0-preview1".to_string();
 
-        pin(&config, Some(version.clone())).unwrap();
+        pin(&config, Some(version.clone()), OutputFormat::Text).unwrap();
 
         let ruby_version_path = config.current_dir.join(".ruby-version");
         let content = std::fs::read_to_string(ruby_version_path).unwrap();
@@ -209,7 +300,7 @@ mod tests {
         let config = test_config().unwrap();
         let version = "1.9.2-p0".to_string();
 
-        pin(&config, Some(version.clone())).unwrap();
+        pin(&config, Some(version.clone()), OutputFormat::Text).unwrap();
 
         let ruby_version_path = config.current_dir.join(".ruby-version");
         let content = std::fs::read_to_string(ruby_version_path).unwrap();
@@ -17,7 +17,7 @@ pub enum Error {
 type Result<T> = miette::Result<T, Error>;
 
 pub fn run(config: &Config, request: &RubyRequest, args: &[String]) -> Result<()> {
-    let Some(ruby) = config.matching_ruby(request) else {
+    let Some(ruby) = config.matching_ruby(&request.clone().into()) else {
         return Err(Error::NoMatchingRuby);
     };
     let (unset, set) = config::env_for(Some(&ruby))?;
@@ -1,4 +1,5 @@
 use anstream::println;
+use bytesize::ByteSize;
 use camino::Utf8PathBuf;
 use owo_colors::OwoColorize;
 use rv_ruby::request::RubyRequest;
@@ -9,6 +10,8 @@ use crate::config::Config;
 pub enum Error {
     #[error("no matching ruby version found")]
     NoMatchingRuby,
+    #[error("{version} is the pinned ruby version, pass --force to uninstall it anyway")]
+    PinnedRuby { version: String },
     #[error(transparent)]
     ConfigError(#[from] crate::config::Error),
     #[error("Could not delete dir {dir}: {error}")]
@@ -21,18 +24,34 @@ pub enum Error {
 type Result<T> = miette::Result<T, Error>;
 
 /// Uninstall the given Ruby version.
-pub async fn uninstall(config: &Config, request: RubyRequest) -> Result<()> {
-    if let Some(ruby) = config.matching_ruby(&request) {
-        let ruby_path = ruby.path;
-        println!("Deleting {}", ruby_path.cyan());
+pub async fn uninstall(config: &Config, request: RubyRequest, force: bool) -> Result<()> {
+    let Some(ruby) = config.matching_ruby(&request.into()) else {
+        return Err(Error::NoMatchingRuby);
+    };
 
-        // Delete the dir at this Ruby version's path.
-        fs_err::remove_dir_all(&ruby_path).map_err(|error| Error::IoError {
-            dir: ruby_path,
-            error,
-        })?;
-        Ok(())
-    } else {
-        Err(Error::NoMatchingRuby)
+    // Refuse to remove the ruby that a pin resolves to, unless forced.
+    if !force
+        && config.requested_ruby.is_some()
+        && config.current_ruby().is_some_and(|current| current == ruby)
+    {
+        return Err(Error::PinnedRuby {
+            version: ruby.display_name(),
+        });
     }
+
+    let ruby_path = ruby.path;
+    println!("Deleting {}", ruby_path.cyan());
+
+    // Delete the dir at this Ruby version's path.
+    let removal = rv_cache::rm_rf(&ruby_path).map_err(|error| Error::IoError {
+        dir: ruby_path,
+        error,
+    })?;
+    let num_bytes_freed = ByteSize::b(removal.bytes).display().iec_short();
+    println!(
+        "Removed {} directories, totalling {}",
+        removal.dirs.cyan(),
+        num_bytes_freed.cyan()
+    );
+    Ok(())
 }
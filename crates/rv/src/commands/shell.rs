@@ -13,9 +13,16 @@ pub struct ShellArgs {
 
 #[derive(Subcommand)]
 pub enum ShellCommand {
-    #[command(about = "Configure your shell to use rv")]
+    #[command(
+        about = "Configure your shell to use rv",
+        after_help = concat!(
+            "Nushell can't evaluate this output directly, so save it to an autoload file once:\n",
+            "  rv shell init nu | save -f ($nu.data-dir | path join \"vendor/autoload/rv.nu\")\n",
+            "Nushell sources that file on startup, which registers the hook.",
+        )
+    )]
     Init {
-        /// The shell to initialize (zsh, bash and fish so far)
+        /// The shell to initialize (zsh, bash, fish and nu so far)
         shell: Shell,
     },
     #[command(about = "Configure shell completions to use rv")]
@@ -1,3 +1,5 @@
+use camino::Utf8Path;
+
 use super::Shell;
 use crate::config::Config;
 
@@ -10,9 +12,15 @@ pub enum Error {
 type Result<T> = miette::Result<T, Error>;
 
 pub fn init(config: &Config, shell: Shell) -> Result<()> {
+    print!("{}", init_script(&config.current_exe, shell));
+    Ok(())
+}
+
+/// The script which the given shell should run to set up rv.
+fn init_script(rv: &Utf8Path, shell: Shell) -> String {
     match shell {
         Shell::Zsh => {
-            print!(
+            format!(
                 concat!(
                     "autoload -U add-zsh-hook\n",
                     "_rv_autoload_hook () {{\n",
@@ -21,12 +29,11 @@ pub fn init(config: &Config, shell: Shell) -> Result<()> {
                     "add-zsh-hook chpwd _rv_autoload_hook\n",
                     "_rv_autoload_hook\n",
                 ),
-                config.current_exe
-            );
-            Ok(())
+                rv
+            )
         }
         Shell::Bash => {
-            print!(
+            format!(
                 concat!(
                     "_rv_autoload_hook() {{\n",
                     "    eval \"$({} shell env bash)\"\n",
@@ -39,14 +46,17 @@ pub fn init(config: &Config, shell: Shell) -> Result<()> {
                     "    fi\n",
                     "}}\n",
                     "_OLDPWD=\"$PWD\"\n",
-                    "PROMPT_COMMAND=\"_chpwd_hook${{PROMPT_COMMAND:+; $PROMPT_COMMAND}}\"\n",
+                    // Don't add the hook twice if the shell runs this again, e.g. when
+                    // re-sourcing its rc file.
+                    "if [[ \";${{PROMPT_COMMAND:-}};\" != *\";_chpwd_hook;\"* ]]; then\n",
+                    "    PROMPT_COMMAND=\"_chpwd_hook${{PROMPT_COMMAND:+; $PROMPT_COMMAND}}\"\n",
+                    "fi\n",
                 ),
-                config.current_exe
-            );
-            Ok(())
+                rv
+            )
         }
         Shell::Fish => {
-            print!(
+            format!(
                 concat!(
                     "function _rv_autoload_hook --on-variable PWD --description 'Change Ruby version on directory change using rv'\n",
                     "    status --is-command-substitution; and return\n",
@@ -54,14 +64,13 @@ pub fn init(config: &Config, shell: Shell) -> Result<()> {
                     "end\n",
                     "_rv_autoload_hook\n"
                 ),
-                config.current_exe
-            );
-            Ok(())
+                rv
+            )
         }
         Shell::Nu => {
             // See Nushell's example for a change-of-directory hook:
             // <https://www.nushell.sh/book/hooks.html#automatically-activating-an-environment-when-entering-a-directory>
-            print!(
+            format!(
                 concat!(
                     "$env.config = ($env.config | upsert hooks.env_change.PWD {{\n",
                     "    [\n",
@@ -71,9 +80,27 @@ pub fn init(config: &Config, shell: Shell) -> Result<()> {
                     "    ]\n",
                     "}})\n",
                 ),
-                config.current_exe
-            );
-            Ok(())
+                rv
+            )
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zsh_init_registers_chpwd_hook() {
+        let script = init_script(Utf8Path::new("/bin/rv"), Shell::Zsh);
+        assert!(script.contains("add-zsh-hook chpwd _rv_autoload_hook"));
+        assert!(script.contains("eval \"$(/bin/rv shell env zsh)\""));
+    }
+
+    #[test]
+    fn nu_init_registers_pwd_hook() {
+        let script = init_script(Utf8Path::new("/bin/rv"), Shell::Nu);
+        assert!(script.contains("upsert hooks.env_change.PWD"));
+        assert!(script.contains("/bin/rv shell env nu | from json | load-env"));
+    }
+}
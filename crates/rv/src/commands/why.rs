@@ -0,0 +1,51 @@
+use anstream::println;
+use camino::Utf8PathBuf;
+use owo_colors::OwoColorize;
+
+use crate::config::Config;
+
+#[derive(Debug, thiserror::Error, miette::Diagnostic)]
+pub enum Error {
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    ParseError(#[from] rv_lockfile::ParseErrors),
+    #[error("{gem} is not in {}", lockfile.cyan())]
+    NotLocked { gem: String, lockfile: Utf8PathBuf },
+}
+
+type Result<T> = miette::Result<T, Error>;
+
+/// Print every chain of dependencies which leads from the Gemfile to `gem`.
+pub fn why(config: &Config, gem: &str) -> Result<()> {
+    let lockfile_path = match &config.gemfile {
+        Some(gemfile) => Utf8PathBuf::from(format!("{gemfile}.lock")),
+        None => config.current_dir.join("Gemfile.lock"),
+    };
+    let contents = fs_err::read_to_string(&lockfile_path)?;
+    let lockfile = rv_lockfile::parse(&contents)?;
+    if !lockfile.specs().any(|spec| spec.gem_version.name == gem) {
+        return Err(Error::NotLocked {
+            gem: gem.to_owned(),
+            lockfile: lockfile_path,
+        });
+    }
+
+    let graph = lockfile.dependency_graph();
+    let paths = graph.paths_to(gem);
+    if paths.is_empty() {
+        println!(
+            "{} is locked, but nothing in the Gemfile depends on it",
+            gem.cyan()
+        );
+    }
+    for path in paths {
+        if let [root] = path.as_slice() {
+            println!("{} is in the Gemfile", root.cyan());
+        } else {
+            println!("{}", path.join(" > "));
+        }
+    }
+    Ok(())
+}
@@ -9,7 +9,8 @@ use tracing::{debug, instrument};
 
 use rv_ruby::{
     Ruby,
-    request::{RequestError, RubyRequest, Source},
+    request::{RubyRequest, Source},
+    requirement::{VersionRequirement, VersionRequirementError},
 };
 
 mod ruby_cache;
@@ -21,7 +22,7 @@ pub enum Error {
     #[error(transparent)]
     IoError(#[from] std::io::Error),
     #[error(transparent)]
-    RequestError(#[from] RequestError),
+    RequirementError(#[from] VersionRequirementError),
     #[error(transparent)]
     EnvError(#[from] std::env::VarError),
     #[error(transparent)]
@@ -38,7 +39,7 @@ pub struct Config {
     pub current_dir: Utf8PathBuf,
     pub cache: rv_cache::Cache,
     pub current_exe: Utf8PathBuf,
-    pub requested_ruby: Option<(RubyRequest, Source)>,
+    pub requested_ruby: Option<(VersionRequirement, Source)>,
 }
 
 impl Config {
@@ -47,12 +48,13 @@ impl Config {
         self.discover_rubies()
     }
 
-    pub fn matching_ruby(&self, request: &RubyRequest) -> Option<Ruby> {
+    /// The highest installed Ruby satisfying `request`.
+    pub fn matching_ruby(&self, request: &VersionRequirement) -> Option<Ruby> {
         let rubies = self.rubies();
         rubies
             .into_iter()
             .rev()
-            .find(|ruby| request.satisfied_by(ruby))
+            .find(|ruby| request.matches(&ruby.version))
     }
 
     pub fn current_ruby(&self) -> Option<Ruby> {
@@ -63,11 +65,11 @@ impl Config {
         }
     }
 
-    pub fn ruby_request(&self) -> Result<RubyRequest> {
+    pub fn ruby_request(&self) -> Result<VersionRequirement> {
         if let Some(request) = &self.requested_ruby {
             Ok(request.0.clone())
         } else {
-            Ok(RubyRequest::default())
+            Ok(RubyRequest::default().into())
         }
     }
 }
@@ -123,10 +125,39 @@ pub fn default_ruby_dirs(root: &Utf8Path) -> Vec<Utf8PathBuf> {
         .collect()
 }
 
+/// The file `rv ruby pin --global` writes the Ruby version used outside of any project to.
+pub fn global_ruby_version_path(root: &Utf8Path) -> Utf8PathBuf {
+    rv_dirs::user_state_dir(root).join("version")
+}
+
+/// Find the Ruby version requested for `current_dir`, falling back to the global one if no
+/// project directory requests one. Requests can be version prefixes, like `3.4`, or RubyGems
+/// constraints, like `~> 3.3.0` or `>= 3.2, < 3.4`.
 pub fn find_requested_ruby(
     current_dir: Utf8PathBuf,
     root: Utf8PathBuf,
-) -> Result<Option<(RubyRequest, Source)>> {
+) -> Result<Option<(VersionRequirement, Source)>> {
+    if let Some(requested) = find_project_ruby(current_dir, &root)? {
+        return Ok(Some(requested));
+    }
+
+    let global_ruby_version = global_ruby_version_path(&root);
+    if global_ruby_version.exists() {
+        debug!("Using the global Ruby version in {}", global_ruby_version);
+        let ruby_version_string = std::fs::read_to_string(&global_ruby_version)?;
+        return Ok(Some((
+            ruby_version_string.parse()?,
+            Source::Global(global_ruby_version),
+        )));
+    }
+
+    Ok(None)
+}
+
+fn find_project_ruby(
+    current_dir: Utf8PathBuf,
+    root: &Utf8Path,
+) -> Result<Option<(VersionRequirement, Source)>> {
     debug!("Searching for project directory in {}", current_dir);
     let mut project_dir = current_dir.clone();
 
@@ -144,15 +175,8 @@ pub fn find_requested_ruby(
         let tools_versions = project_dir.join(".tool-versions");
         if tools_versions.exists() {
             let tools_versions_string = std::fs::read_to_string(&tools_versions)?;
-            let mut tools_version = None;
 
-            for line in tools_versions_string.lines() {
-                if line.trim_start().starts_with("ruby ") {
-                    tools_version = line.trim_start().strip_prefix("ruby ")
-                }
-            }
-
-            if let Some(version) = tools_version {
+            if let Some(version) = tool_versions_ruby(&tools_versions_string) {
                 return Ok(Some((
                     version.parse()?,
                     Source::DotToolVersions(tools_versions),
@@ -160,7 +184,7 @@ pub fn find_requested_ruby(
             }
         }
 
-        if project_dir == root {
+        if project_dir == *root {
             debug!("Reached root {} without finding a project directory", root);
             return Ok(None);
         }
@@ -177,6 +201,25 @@ pub fn find_requested_ruby(
     }
 }
 
+/// Find the ruby version in the contents of an asdf `.tool-versions` file.
+/// When a line lists several versions, the first one is preferred, as in asdf.
+fn tool_versions_ruby(contents: &str) -> Option<&str> {
+    contents.lines().find_map(|line| {
+        let line = line.split_once('#').map_or(line, |(line, _comment)| line);
+        let versions = line.trim().strip_prefix("ruby")?;
+        if !versions.starts_with(char::is_whitespace) {
+            return None;
+        }
+        // Constraints like `>= 3.2, < 3.4` contain spaces, so they take up the rest of the line.
+        let versions = versions.trim();
+        if versions.starts_with(['<', '>', '=', '!', '~']) {
+            Some(versions)
+        } else {
+            versions.split_whitespace().next()
+        }
+    })
+}
+
 const ENV_VARS: [&str; 7] = [
     "RUBY_ROOT",
     "RUBY_ENGINE",
@@ -278,6 +321,24 @@ mod tests {
         default_ruby_dirs(&root);
     }
 
+    #[test]
+    fn test_tool_versions_ruby() {
+        assert_eq!(
+            tool_versions_ruby("nodejs 20.1.0\nruby 3.3.4\n"),
+            Some("3.3.4")
+        );
+        assert_eq!(
+            tool_versions_ruby("ruby\t3.3.4  3.2.0 # primary"),
+            Some("3.3.4")
+        );
+        assert_eq!(tool_versions_ruby("# ruby 3.3.4\nrubyx 1.0\n"), None);
+        assert_eq!(tool_versions_ruby("ruby"), None);
+        assert_eq!(
+            tool_versions_ruby("ruby >= 3.2, < 3.4 # range\n"),
+            Some(">= 3.2, < 3.4")
+        );
+    }
+
     #[test]
     fn test_find_requested_ruby() {
         let root = Utf8PathBuf::from(TempDir::new().unwrap().path().to_str().unwrap());
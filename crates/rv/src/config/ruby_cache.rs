@@ -1,4 +1,4 @@
-use camino::Utf8Path;
+use camino::{Utf8Path, Utf8PathBuf};
 use miette::{IntoDiagnostic, Result};
 use rayon::prelude::*;
 use rayon_tracing::TracedIndexedParallelIterator;
@@ -91,10 +91,10 @@ impl Config {
         Ok(rv_cache::cache_digest((ruby_path, ruby_timestamp)))
     }
 
-    /// Discover all Ruby installations from configured directories with caching
-    pub fn discover_rubies(&self) -> Vec<Ruby> {
-        // Collect all potential Ruby paths first
-        let ruby_paths: Vec<_> = self
+    /// Every directory inside the configured Ruby directories, each of which should hold
+    /// a Ruby installation.
+    pub(crate) fn ruby_install_paths(&self) -> Vec<Utf8PathBuf> {
+        let mut ruby_paths: Vec<_> = self
             .ruby_dirs
             .iter()
             .filter(|ruby_dir| ruby_dir.exists())
@@ -104,16 +104,28 @@ impl Config {
                     .into_iter()
                     .flatten()
                     .filter_map(|entry| {
-                        entry.ok().and_then(|entry| {
-                            entry
-                                .metadata()
-                                .ok()
-                                .filter(|metadata| metadata.is_dir())
-                                .map(|_| entry.path().to_path_buf())
-                        })
+                        // Hidden directories are unfinished installs, not Rubies.
+                        entry
+                            .ok()
+                            .filter(|entry| !entry.file_name().starts_with('.'))
+                            .and_then(|entry| {
+                                entry
+                                    .metadata()
+                                    .ok()
+                                    .filter(|metadata| metadata.is_dir())
+                                    .map(|_| entry.path().to_path_buf())
+                            })
                     })
             })
             .collect();
+        ruby_paths.sort();
+        ruby_paths
+    }
+
+    /// Discover all Ruby installations from configured directories with caching
+    pub fn discover_rubies(&self) -> Vec<Ruby> {
+        // Collect all potential Ruby paths first
+        let ruby_paths = self.ruby_install_paths();
 
         // Process Ruby paths in parallel for better performance
         let mut rubies: Vec<Ruby> = ruby_paths
@@ -10,17 +10,24 @@ use rv_cache::CacheArgs;
 use tokio::main;
 use tracing::debug;
 use tracing_indicatif::IndicatifLayer;
-use tracing_subscriber::{EnvFilter, layer::SubscriberExt as _, util::SubscriberInitExt as _};
+use tracing_subscriber::{
+    EnvFilter, Layer as _, layer::SubscriberExt as _, util::SubscriberInitExt as _,
+};
 
 pub mod commands;
 pub mod config;
+mod json_log;
 
-use crate::commands::cache::{CacheCommand, CacheCommandArgs, cache_clean, cache_dir, cache_prune};
+use crate::commands::cache::{
+    CacheCommand, CacheCommandArgs, cache_clean, cache_dir, cache_prune, cache_verify,
+};
+use crate::commands::doctor::doctor;
+use crate::commands::lock::{LockCommand, LockCommandArgs, lock_diff};
 use crate::commands::ruby::dir::dir as ruby_dir;
 use crate::commands::ruby::find::find as ruby_find;
 use crate::commands::ruby::install::install as ruby_install;
-use crate::commands::ruby::list::list as ruby_list;
-use crate::commands::ruby::pin::pin as ruby_pin;
+use crate::commands::ruby::list::{OutputFormat, list as ruby_list};
+use crate::commands::ruby::pin::{pin as ruby_pin, pin_global as ruby_pin_global};
 #[cfg(unix)]
 use crate::commands::ruby::run::run as ruby_run;
 use crate::commands::ruby::uninstall::uninstall as ruby_uninstall;
@@ -29,6 +36,8 @@ use crate::commands::shell::completions::shell_completions;
 use crate::commands::shell::env::env as shell_env;
 use crate::commands::shell::init::init as shell_init;
 use crate::commands::shell::{ShellArgs, ShellCommand};
+use crate::commands::why::why;
+use crate::json_log::JsonLogFormat;
 
 const STYLES: Styles = Styles::styled()
     .header(AnsiColor::Green.on_default().bold())
@@ -64,6 +73,10 @@ struct Cli {
     #[arg(long, env = "RV_COLOR")]
     color: Option<ColorMode>,
 
+    /// How to format log messages
+    #[arg(long, env = "RV_LOG_FORMAT", value_enum, default_value_t = LogFormat::Pretty)]
+    log_format: LogFormat,
+
     #[command(flatten)]
     cache_args: CacheArgs,
 
@@ -128,6 +141,23 @@ enum Commands {
     Cache(CacheCommandArgs),
     #[command(about = "Configure your shell to use rv")]
     Shell(ShellArgs),
+    #[command(about = "Inspect Gemfile.lock files")]
+    Lock(LockCommandArgs),
+    #[command(about = "Check the cache and Ruby installations for problems")]
+    Doctor,
+    #[command(about = "Show which gems in the Gemfile depend on a locked gem")]
+    Why {
+        /// The gem to explain
+        gem: String,
+    },
+}
+
+#[derive(Debug, Copy, Clone, clap::ValueEnum)]
+pub(crate) enum LogFormat {
+    /// Human-readable lines.
+    Pretty,
+    /// One JSON object per line, for log aggregators.
+    Json,
 }
 
 #[derive(Debug, Copy, Clone, clap::ValueEnum)]
@@ -199,6 +229,12 @@ pub enum Error {
     InitError(#[from] commands::shell::init::Error),
     #[error(transparent)]
     EnvError(#[from] commands::shell::env::Error),
+    #[error(transparent)]
+    LockError(#[from] commands::lock::Error),
+    #[error(transparent)]
+    DoctorError(#[from] commands::doctor::Error),
+    #[error(transparent)]
+    WhyError(#[from] commands::why::Error),
 }
 
 type Result<T> = miette::Result<T, Error>;
@@ -250,15 +286,21 @@ async fn run() -> Result<()> {
         .with_default_directive(cli.verbose.tracing_level_filter().into())
         .from_env()?;
 
-    let reg = tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::fmt::layer()
-                .without_time()
-                // NOTE: We don't need `with_ansi` here since our writer is
-                // an `anstream::AutoStream` that handles color output for us.
-                .with_writer(writer),
-        )
-        .with(filter);
+    let fmt_layer = match cli.log_format {
+        LogFormat::Pretty => tracing_subscriber::fmt::layer()
+            .without_time()
+            // NOTE: We don't need `with_ansi` here since our writer is
+            // an `anstream::AutoStream` that handles color output for us.
+            .with_writer(writer)
+            .boxed(),
+        // Unlike the pretty output, these are timestamped, since aggregators need that.
+        LogFormat::Json => tracing_subscriber::fmt::layer()
+            .event_format(JsonLogFormat)
+            .with_writer(writer)
+            .boxed(),
+    };
+
+    let reg = tracing_subscriber::registry().with(fmt_layer).with(filter);
 
     if std::env::var("RV_DISABLE_INDICATIF").is_err() {
         reg.with(indicatif_layer).init();
@@ -272,28 +314,50 @@ async fn run() -> Result<()> {
         None => {}
         Some(cmd) => match cmd {
             Commands::Ruby(ruby) => match ruby.command {
-                RubyCommand::Find { request } => ruby_find(&config, &request)?,
+                RubyCommand::Find { request, version } => ruby_find(&config, &request.or(version))?,
                 RubyCommand::List {
                     format,
+                    json,
                     installed_only,
-                } => ruby_list(&config, format, installed_only).await?,
-                RubyCommand::Pin { version_request } => ruby_pin(&config, version_request)?,
+                } => {
+                    let format = if json { OutputFormat::Json } else { format };
+                    ruby_list(&config, format, installed_only).await?
+                }
+                RubyCommand::Pin {
+                    version_request,
+                    json,
+                    global,
+                } => {
+                    let format = if json {
+                        OutputFormat::Json
+                    } else {
+                        OutputFormat::Text
+                    };
+                    if global {
+                        ruby_pin_global(&config, version_request, format)?
+                    } else {
+                        ruby_pin(&config, version_request, format)?
+                    }
+                }
                 RubyCommand::Dir => ruby_dir(&config),
                 RubyCommand::Install {
                     version,
                     install_dir,
                     tarball_path,
-                } => ruby_install(&config, install_dir, version, tarball_path).await?,
+                    force,
+                } => ruby_install(&config, install_dir, version, tarball_path, force).await?,
                 RubyCommand::Uninstall {
                     version: version_request,
-                } => ruby_uninstall(&config, version_request).await?,
+                    force,
+                } => ruby_uninstall(&config, version_request, force).await?,
                 #[cfg(unix)]
                 RubyCommand::Run { version, args } => ruby_run(&config, &version, &args)?,
             },
             Commands::Cache(cache) => match cache.command {
-                CacheCommand::Dir => cache_dir(&config)?,
+                CacheCommand::Dir { bucket } => cache_dir(&config, bucket)?,
                 CacheCommand::Clean => cache_clean(&config)?,
-                CacheCommand::Prune => cache_prune(&config)?,
+                CacheCommand::Prune { dry_run } => cache_prune(&config, dry_run)?,
+                CacheCommand::Verify { fix } => cache_verify(&config, fix)?,
             },
             Commands::Shell(shell) => match shell.command {
                 ShellCommand::Init { shell } => shell_init(&config, shell)?,
@@ -302,6 +366,11 @@ async fn run() -> Result<()> {
                 }
                 ShellCommand::Env { shell } => shell_env(&config, shell)?,
             },
+            Commands::Lock(lock) => match lock.command {
+                LockCommand::Diff { old, new, json } => lock_diff(&old, &new, json)?,
+            },
+            Commands::Doctor => doctor(&config)?,
+            Commands::Why { gem } => why(&config, &gem)?,
         },
     }
 
@@ -0,0 +1,30 @@
+use crate::common::RvTest;
+
+#[test]
+fn test_cache_clean() {
+    let mut test = RvTest::new();
+    test.use_cache_dir();
+    let cache_dir = test.temp_dir.path().join("cache");
+    fs_err::create_dir_all(cache_dir.join("gem-v0")).unwrap();
+    fs_err::write(cache_dir.join("gem-v0").join("rack-3.1.0"), "rack").unwrap();
+
+    let output = test.rv(&["cache", "clean"]);
+    output.assert_success();
+    assert!(!cache_dir.join("gem-v0").exists());
+}
+
+#[test]
+fn test_cache_clean_without_cache() {
+    let mut test = RvTest::new();
+    test.use_cache_dir();
+
+    let output = test.rv(&["cache", "clean"]);
+    output.assert_success();
+    assert_eq!(
+        output.normalized_stdout(),
+        "Removed 0 directories, totalling 0 B\n"
+    );
+
+    // Cleaning doesn't create a cache just to lock it.
+    assert!(!test.temp_dir.path().join("cache").exists());
+}
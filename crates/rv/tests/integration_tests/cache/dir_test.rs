@@ -0,0 +1,55 @@
+use crate::common::{RvOutput, RvTest};
+
+impl RvTest {
+    pub fn cache_dir(&self, args: &[&str]) -> RvOutput {
+        let mut cmd = self.rv_command();
+        cmd.args(["cache", "dir"]);
+        cmd.args(args);
+
+        let output = cmd.output().expect("Failed to execute rv command");
+        RvOutput::new(self.temp_dir.path().as_str(), output)
+    }
+
+    /// Use a persistent cache inside the test directory, instead of a temporary one.
+    pub fn use_cache_dir(&mut self) {
+        self.env.remove("RV_NO_CACHE");
+        let cache_dir = self.temp_dir.path().join("cache");
+        self.env
+            .insert("RV_CACHE_DIR".into(), cache_dir.into_string());
+    }
+}
+
+#[test]
+fn test_cache_dir() {
+    let mut test = RvTest::new();
+    test.use_cache_dir();
+    let output = test.cache_dir(&[]);
+    output.assert_success();
+    assert_eq!(output.normalized_stdout(), "/cache\n");
+    assert_eq!(output.normalized_stderr(), "");
+    assert!(
+        test.temp_dir
+            .path()
+            .join("cache")
+            .join(".gitignore")
+            .exists()
+    );
+}
+
+#[test]
+fn test_cache_dir_bucket() {
+    let mut test = RvTest::new();
+    test.use_cache_dir();
+    let output = test.cache_dir(&["--bucket", "ruby"]);
+    output.assert_success();
+    assert_eq!(output.normalized_stdout(), "/cache/ruby-v0\n");
+}
+
+#[test]
+fn test_cache_dir_temporary() {
+    let test = RvTest::new();
+    let output = test.cache_dir(&[]);
+    output.assert_success();
+    assert!(!output.stdout().trim().is_empty());
+    assert!(output.stderr().contains("temporary directory"));
+}
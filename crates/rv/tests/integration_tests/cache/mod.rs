@@ -0,0 +1,3 @@
+mod clean_test;
+mod dir_test;
+mod verify_test;
@@ -0,0 +1,66 @@
+use crate::common::{RvOutput, RvTest};
+
+const VALID_GEM: &[u8] =
+    include_bytes!("../../../../rv-gem-package/tests/fixtures/test-gem-1.0.0.gem");
+
+impl RvTest {
+    pub fn cache_verify(&self, args: &[&str]) -> RvOutput {
+        let mut cmd = self.rv_command();
+        cmd.args(["cache", "verify"]);
+        cmd.args(args);
+
+        let output = cmd.output().expect("Failed to execute rv command");
+        RvOutput::new(self.temp_dir.path().as_str(), output)
+    }
+}
+
+fn setup() -> RvTest {
+    let mut test = RvTest::new();
+    test.use_cache_dir();
+    let bucket = test.temp_dir.path().join("cache").join("gem-v0");
+    fs_err::create_dir_all(&bucket).unwrap();
+    fs_err::write(bucket.join("test-gem-1.0.0.gem"), VALID_GEM).unwrap();
+    fs_err::write(bucket.join("corrupt-1.0.0.gem"), [0xde; 1024]).unwrap();
+    // A gem that's still being written to the cache.
+    fs_err::write(bucket.join(".tmpXyZ123"), [0xde; 512]).unwrap();
+    test
+}
+
+#[test]
+fn test_cache_verify() {
+    let test = setup();
+    let output = test.cache_verify(&[]);
+    output.assert_success();
+
+    let stdout = output.normalized_stdout();
+    assert!(stdout.contains("Corrupt /cache/gem-v0/corrupt-1.0.0.gem: "));
+    assert!(!stdout.contains("test-gem-1.0.0.gem"));
+    assert!(stdout.ends_with("Verified 2 cached gems, 1 corrupt\n"));
+
+    // Without --fix, nothing is removed.
+    let bucket = test.temp_dir.path().join("cache").join("gem-v0");
+    assert!(bucket.join("corrupt-1.0.0.gem").exists());
+}
+
+#[test]
+fn test_cache_verify_fix() {
+    let test = setup();
+    let output = test.cache_verify(&["--fix"]);
+    output.assert_success();
+
+    let stdout = output.normalized_stdout();
+    assert!(stdout.contains("Corrupt /cache/gem-v0/corrupt-1.0.0.gem: "));
+    assert!(stdout.ends_with("Verified 2 cached gems, 1 corrupt, all removed\n"));
+
+    let bucket = test.temp_dir.path().join("cache").join("gem-v0");
+    assert!(!bucket.join("corrupt-1.0.0.gem").exists());
+    assert!(bucket.join("test-gem-1.0.0.gem").exists());
+    assert!(bucket.join(".tmpXyZ123").exists());
+
+    let output = test.cache_verify(&[]);
+    output.assert_success();
+    assert_eq!(
+        output.normalized_stdout(),
+        "Verified 1 cached gems, 0 corrupt\n"
+    );
+}
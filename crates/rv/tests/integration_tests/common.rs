@@ -1,7 +1,11 @@
 use camino::Utf8PathBuf;
 use camino_tempfile_ext::camino_tempfile::Utf8TempDir;
 use mockito::Mock;
-use std::{collections::HashMap, process::Command};
+use std::{
+    collections::HashMap,
+    io::Write,
+    process::{Command, Stdio},
+};
 
 pub struct RvTest {
     pub temp_dir: Utf8TempDir,
@@ -59,6 +63,29 @@ impl RvTest {
         cmd
     }
 
+    /// Run `shell` with `input` on its stdin, as if it was typed in, with the same environment
+    /// as rv. Interactive shells run their prompt hooks between commands, like in a terminal.
+    pub fn shell(&self, shell: &str, args: &[&str], input: &str) -> RvOutput {
+        let mut cmd = Command::new(shell);
+        cmd.args(args)
+            .current_dir(&self.cwd)
+            .env_clear()
+            .envs(&self.env)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        let mut child = cmd.spawn().expect("Failed to start shell");
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(input.as_bytes())
+            .expect("Failed to write to shell");
+        let output = child.wait_with_output().expect("Failed to run shell");
+        RvOutput::new(self.temp_dir.path().as_str(), output)
+    }
+
     /// Mocks the /releases API endpoint. Returns the mock handle
     /// so that tests can optionally assert it was called.
     pub fn mock_releases(&mut self, body: &str) -> Mock {
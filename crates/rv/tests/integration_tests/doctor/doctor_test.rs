@@ -0,0 +1,44 @@
+use crate::common::{RvOutput, RvTest};
+
+impl RvTest {
+    pub fn doctor(&self) -> RvOutput {
+        self.rv(&["doctor"])
+    }
+}
+
+#[test]
+fn test_doctor_healthy() {
+    let test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.5");
+    let doctor = test.doctor();
+    doctor.assert_success();
+    assert_eq!(
+        doctor.normalized_stdout(),
+        "✓ Caching is disabled\n\
+         ✓ ruby-3.3.5 at /opt/rubies/ruby-3.3.5\n\
+         ✓ ruby resolves to /opt/rubies/ruby-3.3.5/bin/ruby\n"
+    );
+}
+
+#[test]
+fn test_doctor_broken_ruby() {
+    let test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.5");
+    // An install which was interrupted before its executable was extracted.
+    std::fs::create_dir_all(test.temp_dir.path().join("opt/rubies/ruby-3.4.1")).unwrap();
+    std::fs::write(test.temp_dir.path().join(".ruby-version"), "3.4.1").unwrap();
+
+    let doctor = test.doctor();
+    doctor.assert_failure();
+    assert_eq!(
+        doctor.normalized_stdout(),
+        "✓ Caching is disabled\n\
+         ✓ ruby-3.3.5 at /opt/rubies/ruby-3.3.5\n\
+         ✗ /opt/rubies/ruby-3.4.1 is broken: No ruby executable found in bin/ directory\n\
+         ✗ ruby-3.4.1 (from /.ruby-version) is not installed\n"
+    );
+    assert_eq!(
+        doctor.normalized_stderr(),
+        "Error: DoctorError(ChecksFailed { failed: 2, total: 4 })\n"
+    );
+}
@@ -0,0 +1 @@
+mod doctor_test;
@@ -0,0 +1,88 @@
+use crate::common::{RvOutput, RvTest};
+
+const OLD_LOCKFILE: &str = "\
+GEM
+  remote: https://rubygems.org/
+  specs:
+    rack (3.1.0)
+    rake (13.2.1)
+
+PLATFORMS
+  ruby
+
+DEPENDENCIES
+  rack
+  rake
+
+BUNDLED WITH
+   2.6.9
+";
+
+const NEW_LOCKFILE: &str = "\
+GEM
+  remote: https://rubygems.org/
+  specs:
+    puma (6.4.0)
+      nio4r (~> 2.0)
+    nio4r (2.7.4)
+    rack (3.1.1)
+    rake (13.2.1)
+
+PLATFORMS
+  ruby
+
+DEPENDENCIES
+  puma
+  rack
+  rake
+
+BUNDLED WITH
+   2.6.9
+";
+
+impl RvTest {
+    pub fn lock_diff(&self, args: &[&str]) -> RvOutput {
+        std::fs::write(self.temp_dir.path().join("Gemfile.lock.old"), OLD_LOCKFILE).unwrap();
+        std::fs::write(self.temp_dir.path().join("Gemfile.lock.new"), NEW_LOCKFILE).unwrap();
+
+        let mut cmd = self.rv_command();
+        cmd.args(["lock", "diff", "Gemfile.lock.old", "Gemfile.lock.new"]);
+        cmd.args(args);
+
+        let output = cmd.output().expect("Failed to execute rv command");
+        RvOutput::new(self.temp_dir.path().as_str(), output)
+    }
+}
+
+#[test]
+fn test_lock_diff() {
+    let test = RvTest::new();
+    let diff = test.lock_diff(&[]);
+    diff.assert_success();
+    assert_eq!(
+        diff.normalized_stdout(),
+        "+ nio4r (2.7.4)\n+ puma (6.4.0)\n~ rack (3.1.0 -> 3.1.1)\n"
+    );
+}
+
+#[test]
+fn test_lock_diff_json() {
+    let test = RvTest::new();
+    let diff = test.lock_diff(&["--json"]);
+    diff.assert_success();
+
+    let diff: serde_json::Value = serde_json::from_str(&diff.stdout()).unwrap();
+    assert_eq!(
+        diff,
+        serde_json::json!({
+            "added": [
+                {"name": "nio4r", "platform": null, "version": "2.7.4"},
+                {"name": "puma", "platform": null, "version": "6.4.0"},
+            ],
+            "removed": [],
+            "changed": [
+                {"name": "rack", "platform": null, "old": "3.1.0", "new": "3.1.1"},
+            ],
+        })
+    );
+}
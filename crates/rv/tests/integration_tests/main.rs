@@ -1,3 +1,7 @@
+mod cache;
 mod common;
+mod doctor;
+mod lock;
 mod ruby;
 mod shell;
+mod why;
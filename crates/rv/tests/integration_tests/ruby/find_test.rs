@@ -68,7 +68,7 @@ fn test_ruby_find_dot_ruby_version_empty() {
     find.assert_failure();
     assert_eq!(
         find.normalized_stderr(),
-        "Error: ConfigError(RequestError(EmptyInput))\n"
+        "Error: ConfigError(RequirementError(Request(EmptyInput)))\n"
     );
 }
 
@@ -86,6 +86,73 @@ fn test_ruby_find_dot_ruby_version_matching() {
     );
 }
 
+#[test]
+fn test_ruby_find_dot_tool_versions_matching() {
+    let test = RvTest::new();
+    std::fs::write(
+        test.temp_dir.path().join(".tool-versions"),
+        "nodejs 20.1.0\nruby 3.3.5\n",
+    )
+    .unwrap();
+    test.create_ruby_dir("ruby-3.3.5");
+    test.create_ruby_dir("ruby-3.4.5");
+    let find = test.ruby_find(&[]);
+    find.assert_success();
+    assert_eq!(
+        find.normalized_stdout(),
+        "/opt/rubies/ruby-3.3.5/bin/ruby\n"
+    );
+}
+
+#[test]
+fn test_ruby_find_dot_ruby_version_constraint() {
+    let test = RvTest::new();
+    std::fs::write(test.temp_dir.path().join(".ruby-version"), "~> 3.3.0\n").unwrap();
+    test.create_ruby_dir("ruby-3.3.4");
+    test.create_ruby_dir("ruby-3.3.5");
+    test.create_ruby_dir("ruby-3.4.5");
+    let find = test.ruby_find(&[]);
+    find.assert_success();
+    assert_eq!(
+        find.normalized_stdout(),
+        "/opt/rubies/ruby-3.3.5/bin/ruby\n"
+    );
+}
+
+#[test]
+fn test_ruby_find_dot_tool_versions_range() {
+    let test = RvTest::new();
+    std::fs::write(
+        test.temp_dir.path().join(".tool-versions"),
+        "ruby >= 3.2, < 3.4\n",
+    )
+    .unwrap();
+    test.create_ruby_dir("ruby-3.1.6");
+    test.create_ruby_dir("ruby-3.3.5");
+    test.create_ruby_dir("ruby-3.4.5");
+    let find = test.ruby_find(&[]);
+    find.assert_success();
+    assert_eq!(
+        find.normalized_stdout(),
+        "/opt/rubies/ruby-3.3.5/bin/ruby\n"
+    );
+}
+
+#[test]
+fn test_ruby_find_dot_ruby_version_over_dot_tool_versions() {
+    let test = RvTest::new();
+    std::fs::write(test.temp_dir.path().join(".ruby-version"), "3.4.5\n").unwrap();
+    std::fs::write(test.temp_dir.path().join(".tool-versions"), "ruby 3.3.5\n").unwrap();
+    test.create_ruby_dir("ruby-3.3.5");
+    test.create_ruby_dir("ruby-3.4.5");
+    let find = test.ruby_find(&[]);
+    find.assert_success();
+    assert_eq!(
+        find.normalized_stdout(),
+        "/opt/rubies/ruby-3.4.5/bin/ruby\n"
+    );
+}
+
 #[test]
 fn test_ruby_find_multiple_matching() {
     let test = RvTest::new();
@@ -124,3 +191,24 @@ fn test_ruby_find_matching_jruby() {
         "/opt/rubies/jruby-9.4.8.0/bin/ruby\n"
     );
 }
+
+#[test]
+fn test_ruby_find_json_logs() {
+    let test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.5");
+    let find = test.rv(&["--log-format", "json", "-v", "ruby", "find", "3.3.5"]);
+    find.assert_success();
+    assert_eq!(
+        find.normalized_stdout(),
+        "/opt/rubies/ruby-3.3.5/bin/ruby\n"
+    );
+
+    let stderr = find.stderr();
+    assert!(!stderr.is_empty());
+    for line in stderr.lines() {
+        let log: serde_json::Value = serde_json::from_str(line).unwrap();
+        for key in ["timestamp", "level", "target", "fields"] {
+            assert!(log.get(key).is_some(), "{key} missing from {line}");
+        }
+    }
+}
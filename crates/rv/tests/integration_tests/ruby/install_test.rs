@@ -56,6 +56,163 @@ fn test_ruby_install_successful_download() {
     );
 }
 
+#[test]
+fn test_ruby_install_from_release_asset() {
+    let mut test = RvTest::new();
+
+    // The tarball isn't at the usual download URL, so it has to be found through the release.
+    let asset_name = make_tarball_file_name("3.4.5");
+    let releases_body = format!(
+        r#"{{"name": "20250801", "assets": [{{"name": "{asset_name}", "browser_download_url": "{}/assets/{asset_name}"}}]}}"#,
+        test.server_url()
+    );
+    let releases_mock = test.mock_releases(&releases_body);
+    let tarball_mock = test
+        .mock_tarball_download(
+            &format!("assets/{asset_name}"),
+            &create_ruby_tarball("3.4.5"),
+        )
+        .create();
+
+    let output = test.rv(&["ruby", "install", "3.4.5"]);
+    output.assert_success();
+    releases_mock.assert();
+    tarball_mock.assert();
+
+    let ruby_dir = test
+        .temp_dir
+        .path()
+        .join("tmp/home/.data/rv/rubies/ruby-3.4.5");
+    assert!(ruby_dir.join("bin").join("ruby").exists());
+}
+
+#[test]
+fn test_ruby_install_rejects_checksum_mismatch() {
+    let mut test = RvTest::new();
+
+    let asset_name = make_tarball_file_name("3.4.5");
+    let releases_body = format!(
+        r#"{{"name": "20250801", "assets": [{{"name": "{asset_name}", "browser_download_url": "{}/assets/{asset_name}", "digest": "sha256:{}"}}]}}"#,
+        test.server_url(),
+        "0".repeat(64),
+    );
+    test.mock_releases(&releases_body);
+    let _mock = test
+        .mock_tarball_download(
+            &format!("assets/{asset_name}"),
+            &create_ruby_tarball("3.4.5"),
+        )
+        .create();
+
+    test.env.remove("RV_NO_CACHE");
+    let cache_dir = test.temp_dir.path().join("cache");
+    test.env
+        .insert("RV_CACHE_DIR".into(), cache_dir.as_str().into());
+
+    let output = test.rv(&["ruby", "install", "3.4.5"]);
+    output.assert_failure();
+    assert!(output.stderr().contains("ChecksumMismatch"));
+
+    let rubies_dir = test.temp_dir.path().join("tmp/home/.data/rv/rubies");
+    assert!(!rubies_dir.join("ruby-3.4.5").exists());
+
+    // The bad tarball isn't kept around to be reused.
+    let cache_key = rv_cache::cache_digest(format!("{}/assets/{asset_name}", test.server_url()));
+    let tarball_path = cache_dir
+        .join("ruby-v0")
+        .join("tarballs")
+        .join(format!("{cache_key}.tar.gz"));
+    assert!(!tarball_path.exists());
+}
+
+#[test]
+fn test_ruby_install_failed_extraction_leaves_nothing_behind() {
+    let mut test = RvTest::new();
+
+    // The Ruby is extracted fine, but then an entry tries to escape the rubies dir.
+    let tarball_content = create_ruby_tarball_with_extra_entry("3.4.5", b"../escape");
+    let filename = make_tarball_file_name("3.4.5");
+    let tarball_file = test.mock_tarball_on_disk(&filename, &tarball_content);
+
+    let output = test.rv(&[
+        "ruby",
+        "install",
+        "--tarball-path",
+        tarball_file.as_str(),
+        "3.4.5",
+    ]);
+    output.assert_failure();
+
+    let rubies_dir = test.temp_dir.path().join("tmp/home/.data/rv/rubies");
+    let leftovers: Vec<_> = fs::read_dir(&rubies_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    assert!(leftovers.is_empty(), "Found leftovers {leftovers:?}");
+}
+
+#[test]
+fn test_ruby_install_force_replaces_broken_install() {
+    let mut test = RvTest::new();
+
+    let filename = make_tarball_file_name("3.4.5");
+    let tarball_file = test.mock_tarball_on_disk(&filename, &create_ruby_tarball("3.4.5"));
+    let ruby_dir = test
+        .temp_dir
+        .path()
+        .join("tmp/home/.data/rv/rubies/ruby-3.4.5");
+    fs::create_dir_all(&ruby_dir).unwrap();
+    fs::write(ruby_dir.join("leftover"), "half-extracted").unwrap();
+
+    let install = |force: bool| {
+        let mut args = vec!["ruby", "install", "--tarball-path", tarball_file.as_str()];
+        if force {
+            args.push("--force");
+        }
+        args.push("3.4.5");
+        test.rv(&args)
+    };
+
+    let output = install(false);
+    output.assert_success();
+    assert!(output.stdout().contains("already installed"));
+    assert!(!ruby_dir.join("bin").join("ruby").exists());
+
+    let output = install(true);
+    output.assert_success();
+    assert!(ruby_dir.join("bin").join("ruby").exists());
+    assert!(!ruby_dir.join("leftover").exists());
+}
+
+#[test]
+fn test_ruby_install_force_keeps_install_if_reinstall_fails() {
+    let mut test = RvTest::new();
+
+    let tarball_file =
+        test.mock_tarball_on_disk(&make_tarball_file_name("3.4.5"), b"not a tarball");
+    let ruby_dir = test
+        .temp_dir
+        .path()
+        .join("tmp/home/.data/rv/rubies/ruby-3.4.5");
+    fs::create_dir_all(ruby_dir.join("bin")).unwrap();
+    fs::write(ruby_dir.join("bin").join("ruby"), "working ruby").unwrap();
+
+    let output = test.rv(&[
+        "ruby",
+        "install",
+        "--tarball-path",
+        tarball_file.as_str(),
+        "--force",
+        "3.4.5",
+    ]);
+
+    output.assert_failure();
+    assert_eq!(
+        fs::read_to_string(ruby_dir.join("bin").join("ruby")).unwrap(),
+        "working ruby"
+    );
+}
+
 #[test]
 fn test_ruby_install_from_tarball() {
     let mut test = RvTest::new();
@@ -395,6 +552,213 @@ fn test_ruby_install_temp_file_cleanup_on_extraction_failure() {
     assert!(!temp_path.exists(), "Temp file should be cleaned up");
 }
 
+#[test]
+fn test_ruby_install_from_tarball_rejects_path_traversal() {
+    let mut test = RvTest::new();
+
+    let tarball_content = create_tarball_with_entry(b"../escape", tar::EntryType::Regular, None);
+    let filename = make_tarball_file_name("3.4.5");
+    let tarball_file = test.mock_tarball_on_disk(&filename, &tarball_content);
+
+    let output = test.rv(&[
+        "ruby",
+        "install",
+        "--tarball-path",
+        tarball_file.as_str(),
+        "3.4.5",
+    ]);
+
+    output.assert_failure();
+    let data_dir = test.temp_dir.path().join("tmp/home/.data/rv");
+    assert!(!data_dir.join("escape").exists());
+}
+
+#[test]
+fn test_ruby_install_from_tarball_rejects_escaping_symlink() {
+    let mut test = RvTest::new();
+
+    let tarball_content =
+        create_tarball_with_entry(b"escape", tar::EntryType::Symlink, Some("../.."));
+    let filename = make_tarball_file_name("3.4.5");
+    let tarball_file = test.mock_tarball_on_disk(&filename, &tarball_content);
+
+    let output = test.rv(&[
+        "ruby",
+        "install",
+        "--tarball-path",
+        tarball_file.as_str(),
+        "3.4.5",
+    ]);
+
+    output.assert_failure();
+    let rubies_dir = test.temp_dir.path().join("tmp/home/.data/rv/rubies");
+    assert!(fs::symlink_metadata(rubies_dir.join("escape")).is_err());
+}
+
+#[test]
+fn test_ruby_install_from_tarball_rejects_escaping_hard_link() {
+    let mut test = RvTest::new();
+
+    // Hard link targets are relative to the directory the tarball's extracted into, which is
+    // inside the rubies dir.
+    let data_dir = test.temp_dir.path().join("tmp/home/.data/rv");
+    fs::create_dir_all(&data_dir).unwrap();
+    fs::write(data_dir.join("victim"), "precious").unwrap();
+
+    let tarball_content =
+        create_tarball_with_entry(b"escape", tar::EntryType::Link, Some("../../victim"));
+    let filename = make_tarball_file_name("3.4.5");
+    let tarball_file = test.mock_tarball_on_disk(&filename, &tarball_content);
+
+    let output = test.rv(&[
+        "ruby",
+        "install",
+        "--tarball-path",
+        tarball_file.as_str(),
+        "3.4.5",
+    ]);
+
+    output.assert_failure();
+    assert!(fs::symlink_metadata(data_dir.join("rubies").join("escape")).is_err());
+    assert_eq!(
+        fs::read_to_string(data_dir.join("victim")).unwrap(),
+        "precious"
+    );
+}
+
+#[test]
+fn test_ruby_install_from_tarball_rejects_chained_symlinks() {
+    let mut test = RvTest::new();
+
+    // Each symlink stays inside the extraction dir on paper, but `a/b/c` really points two levels
+    // above it, into the data dir.
+    let tarball_content = create_tarball_with_entries(&[
+        (b"a", tar::EntryType::Symlink, Some(".")),
+        (b"a/b", tar::EntryType::Symlink, Some("..")),
+        (b"a/b/c", tar::EntryType::Symlink, Some("..")),
+        (b"a/b/c/escape", tar::EntryType::Regular, None),
+    ]);
+    let filename = make_tarball_file_name("3.4.5");
+    let tarball_file = test.mock_tarball_on_disk(&filename, &tarball_content);
+
+    let output = test.rv(&[
+        "ruby",
+        "install",
+        "--tarball-path",
+        tarball_file.as_str(),
+        "3.4.5",
+    ]);
+
+    output.assert_failure();
+    let data_dir = test.temp_dir.path().join("tmp/home/.data/rv");
+    assert!(!data_dir.join("escape").exists());
+    assert!(!data_dir.join("rubies/escape").exists());
+}
+
+/// Build a tarball with a single entry, skipping the path checks that `tar::Builder` does.
+fn create_tarball_with_entry(
+    path: &[u8],
+    entry_type: tar::EntryType,
+    link_name: Option<&str>,
+) -> Vec<u8> {
+    create_tarball_with_entries(&[(path, entry_type, link_name)])
+}
+
+/// Build a tarball with the given entries in order, skipping the path checks that
+/// `tar::Builder` does.
+fn create_tarball_with_entries(entries: &[(&[u8], tar::EntryType, Option<&str>)]) -> Vec<u8> {
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use std::io::Write;
+    use tar::Builder;
+
+    let content = b"escaped";
+    let mut archive_data = Vec::new();
+    {
+        let mut builder = Builder::new(&mut archive_data);
+
+        for &(path, entry_type, link_name) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.as_gnu_mut().unwrap().name[..path.len()].copy_from_slice(path);
+            header.set_entry_type(entry_type);
+            header.set_mode(0o644);
+            if let Some(link_name) = link_name {
+                header.set_link_name(link_name).unwrap();
+                header.set_size(0);
+                header.set_cksum();
+                builder.append(&header, std::io::empty()).unwrap();
+            } else {
+                header.set_size(content.len() as u64);
+                header.set_cksum();
+                builder.append(&header, &content[..]).unwrap();
+            }
+        }
+
+        builder.finish().unwrap();
+    }
+
+    let mut gz_data = Vec::new();
+    {
+        let mut encoder = GzEncoder::new(&mut gz_data, Compression::default());
+        encoder.write_all(&archive_data).unwrap();
+        encoder.finish().unwrap();
+    }
+
+    gz_data
+}
+
+/// Build a tarball laid out like the ones rv-ruby releases, with just a `ruby` executable.
+fn create_ruby_tarball(version: &str) -> Vec<u8> {
+    build_ruby_tarball(version, |_| {})
+}
+
+/// Like [`create_ruby_tarball`], followed by a file at `path`, skipping the path checks that
+/// `tar::Builder` does.
+fn create_ruby_tarball_with_extra_entry(version: &str, path: &[u8]) -> Vec<u8> {
+    build_ruby_tarball(version, |builder| {
+        let mut header = tar::Header::new_gnu();
+        header.as_gnu_mut().unwrap().name[..path.len()].copy_from_slice(path);
+        header.set_size(0);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append(&header, std::io::empty()).unwrap();
+    })
+}
+
+fn build_ruby_tarball(
+    version: &str,
+    extra: impl FnOnce(&mut tar::Builder<flate2::write::GzEncoder<Vec<u8>>>),
+) -> Vec<u8> {
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use tar::Builder;
+
+    let ruby_content = "#!/bin/bash\necho 'mock ruby'\n";
+    let mut builder = Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+    for dir in [
+        format!("rv-ruby@{version}/{version}/"),
+        format!("rv-ruby@{version}/{version}/bin/"),
+    ] {
+        let mut header = tar::Header::new_gnu();
+        header.set_path(dir).unwrap();
+        header.set_size(0);
+        header.set_mode(0o755);
+        header.set_entry_type(tar::EntryType::Directory);
+        header.set_cksum();
+        builder.append(&header, std::io::empty()).unwrap();
+    }
+    let mut header = tar::Header::new_gnu();
+    header
+        .set_path(format!("rv-ruby@{version}/{version}/bin/ruby"))
+        .unwrap();
+    header.set_size(ruby_content.len() as u64);
+    header.set_mode(0o755);
+    header.set_cksum();
+    builder.append(&header, ruby_content.as_bytes()).unwrap();
+    extra(&mut builder);
+    builder.into_inner().unwrap().finish().unwrap()
+}
+
 fn create_mock_tarball() -> Vec<u8> {
     use flate2::Compression;
     use flate2::write::GzEncoder;
@@ -148,3 +148,33 @@ fn test_ruby_list_with_no_installed_rubies_is_empty() {
     // and the API is disabled.
     assert_eq!(output.normalized_stdout(), "");
 }
+
+#[test]
+fn test_ruby_list_json_flags_active_ruby() {
+    let mut test = RvTest::new();
+    test.create_ruby_dir("ruby-3.1.4");
+    test.create_ruby_dir("ruby-3.2.0");
+
+    let project_dir = test.temp_dir.path().join("project");
+    std::fs::create_dir_all(project_dir.as_path()).unwrap();
+    std::fs::write(project_dir.join(".ruby-version"), b"3.1.4").unwrap();
+    test.cwd = project_dir;
+
+    let output = test.ruby_list(&["--json"]);
+    output.assert_success();
+
+    let rubies: serde_json::Value =
+        serde_json::from_str(&output.stdout()).expect("Output should be valid JSON");
+    let rubies: Vec<_> = rubies
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|ruby| {
+            (
+                ruby["version"].as_str().unwrap(),
+                ruby["active"].as_bool().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(rubies, [("ruby-3.1.4", true), ("ruby-3.2.0", false)]);
+}
@@ -1,4 +1,6 @@
 mod find_test;
 mod install_test;
 mod list_test;
+mod pin_test;
 mod uninstall_test;
+mod which_test;
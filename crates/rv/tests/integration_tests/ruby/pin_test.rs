@@ -0,0 +1,137 @@
+use crate::common::{RvOutput, RvTest};
+
+impl RvTest {
+    pub fn ruby_pin(&self, args: &[&str]) -> RvOutput {
+        let mut cmd = self.rv_command();
+        cmd.args(["ruby", "pin"]);
+        cmd.args(args);
+
+        let output = cmd.output().expect("Failed to execute rv command");
+        RvOutput::new(self.temp_dir.path().as_str(), output)
+    }
+}
+
+#[test]
+fn test_ruby_pin_dot_tool_versions() {
+    let test = RvTest::new();
+    let tool_versions = test.temp_dir.path().join(".tool-versions");
+    std::fs::write(&tool_versions, "nodejs 20.1.0\nruby 3.3.4\n").unwrap();
+
+    let pin = test.ruby_pin(&[]);
+    pin.assert_success();
+    assert_eq!(
+        pin.normalized_stdout(),
+        "/.tool-versions is pinned to Ruby ruby-3.3.4\n"
+    );
+
+    let pin = test.ruby_pin(&["3.4.5"]);
+    pin.assert_success();
+    assert_eq!(
+        std::fs::read_to_string(&tool_versions).unwrap(),
+        "nodejs 20.1.0\nruby 3.4.5\n"
+    );
+}
+
+#[test]
+fn test_ruby_pin_json() {
+    let test = RvTest::new();
+
+    let pin = test.ruby_pin(&["--json", "3.4"]);
+    pin.assert_success();
+    let output: serde_json::Value = serde_json::from_str(&pin.stdout()).unwrap();
+    assert_eq!(
+        output,
+        serde_json::json!({
+            "path": test.temp_dir.path().as_str(),
+            "version": "3.4",
+            "wrote": ".ruby-version",
+        })
+    );
+    assert_eq!(
+        std::fs::read_to_string(test.temp_dir.path().join(".ruby-version")).unwrap(),
+        "3.4\n"
+    );
+
+    let pin = test.ruby_pin(&["--json"]);
+    pin.assert_success();
+    let output: serde_json::Value = serde_json::from_str(&pin.stdout()).unwrap();
+    assert_eq!(
+        output,
+        serde_json::json!({
+            "path": test.temp_dir.path().as_str(),
+            "version": "ruby-3.4",
+        })
+    );
+}
+
+#[test]
+fn test_ruby_pin_explicit_version() {
+    let test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.5");
+    let ruby_version = test.temp_dir.path().join(".ruby-version");
+    assert!(!ruby_version.exists());
+
+    let pin = test.ruby_pin(&["3.3"]);
+    pin.assert_success();
+    assert_eq!(
+        pin.normalized_stdout(),
+        "/.ruby-version pinned to Ruby 3.3\n"
+    );
+    assert_eq!(pin.normalized_stderr(), "");
+    assert_eq!(std::fs::read_to_string(&ruby_version).unwrap(), "3.3\n");
+
+    // Pinning a Ruby that isn't installed works, with a warning.
+    let pin = test.ruby_pin(&["3.4.5"]);
+    pin.assert_success();
+    assert!(
+        pin.stderr()
+            .contains("Ruby 3.4.5 is not installed, install it with `rv ruby install 3.4.5`")
+    );
+    assert_eq!(std::fs::read_to_string(&ruby_version).unwrap(), "3.4.5\n");
+}
+
+#[test]
+fn test_ruby_pin_global() {
+    let test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.5");
+    test.create_ruby_dir("ruby-3.4.5");
+
+    let pin = test.ruby_pin(&["--global"]);
+    pin.assert_failure();
+
+    let pin = test.ruby_pin(&["--global", "3.3"]);
+    pin.assert_success();
+    assert_eq!(
+        pin.normalized_stdout(),
+        "/tmp/home/.local/share/rv/version pinned to Ruby 3.3\n"
+    );
+    assert!(!test.temp_dir.path().join(".ruby-version").exists());
+
+    // Without a project pin, the global one is used.
+    let which = test.ruby_which(&[]);
+    which.assert_success();
+    assert_eq!(
+        which.normalized_stdout(),
+        "/opt/rubies/ruby-3.3.5/bin/ruby\n"
+    );
+
+    let pin = test.ruby_pin(&["--global"]);
+    pin.assert_success();
+    assert_eq!(
+        pin.normalized_stdout(),
+        "/tmp/home/.local/share/rv/version is pinned to Ruby 3.3\n"
+    );
+
+    // A project pin takes precedence, and doesn't touch the global one.
+    let pin = test.ruby_pin(&["3.4"]);
+    pin.assert_success();
+    assert_eq!(
+        pin.normalized_stdout(),
+        "/.ruby-version pinned to Ruby 3.4\n"
+    );
+    let which = test.ruby_which(&[]);
+    assert_eq!(
+        which.normalized_stdout(),
+        "/opt/rubies/ruby-3.4.5/bin/ruby\n"
+    );
+}
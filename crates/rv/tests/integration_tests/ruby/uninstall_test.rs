@@ -48,11 +48,27 @@ fn test_ruby_uninstall_no_matching_rubies() {
 #[test]
 fn test_ruby_uninstall_matching_request() {
     let test = RvTest::new();
-    test.create_ruby_dir("ruby-3.3.5");
+    let ruby_dir = test.create_ruby_dir("ruby-3.3.5");
     let uninstall = test.ruby_uninstall(&["3.3.5"]);
     uninstall.assert_success();
     assert_eq!(
         uninstall.normalized_stdout(),
-        "Deleting /opt/rubies/ruby-3.3.5\n"
+        "Deleting /opt/rubies/ruby-3.3.5\nRemoved 2 directories, totalling 101 B\n"
     );
+    assert!(!ruby_dir.exists());
+}
+
+#[test]
+fn test_ruby_uninstall_pinned_ruby() {
+    let test = RvTest::new();
+    let ruby_dir = test.create_ruby_dir("ruby-3.3.5");
+    std::fs::write(test.cwd.join(".ruby-version"), "3.3.5").unwrap();
+
+    let uninstall = test.ruby_uninstall(&["3.3.5"]);
+    assert!(!uninstall.success());
+    assert!(ruby_dir.exists());
+
+    let uninstall = test.ruby_uninstall(&["--force", "3.3.5"]);
+    uninstall.assert_success();
+    assert!(!ruby_dir.exists());
 }
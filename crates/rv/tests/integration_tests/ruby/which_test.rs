@@ -0,0 +1,46 @@
+use crate::common::{RvOutput, RvTest};
+
+impl RvTest {
+    pub fn ruby_which(&self, args: &[&str]) -> RvOutput {
+        let mut cmd = self.rv_command();
+        cmd.args(["ruby", "which"]);
+        cmd.args(args);
+
+        let output = cmd.output().expect("Failed to execute rv command");
+        RvOutput::new(self.temp_dir.path().as_str(), output)
+    }
+}
+
+#[test]
+fn test_ruby_which_dot_ruby_version() {
+    let test = RvTest::new();
+    std::fs::write(test.temp_dir.path().join(".ruby-version"), "3.3.5\n").unwrap();
+    test.create_ruby_dir("ruby-3.3.5");
+    test.create_ruby_dir("ruby-3.4.5");
+
+    let which = test.ruby_which(&[]);
+    which.assert_success();
+    assert_eq!(
+        which.normalized_stdout(),
+        "/opt/rubies/ruby-3.3.5/bin/ruby\n"
+    );
+
+    let which = test.ruby_which(&["--version", "3.4"]);
+    which.assert_success();
+    assert_eq!(
+        which.normalized_stdout(),
+        "/opt/rubies/ruby-3.4.5/bin/ruby\n"
+    );
+}
+
+#[test]
+fn test_ruby_which_no_matching_rubies() {
+    let test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.5");
+    let which = test.ruby_which(&["--version", "3.4"]);
+    which.assert_failure();
+    assert_eq!(
+        which.normalized_stderr(),
+        "Error: FindError(NoMatchingRuby)\n"
+    );
+}
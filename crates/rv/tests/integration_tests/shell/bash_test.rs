@@ -0,0 +1,36 @@
+use crate::common::RvTest;
+use indoc::indoc;
+
+#[test]
+fn test_bash_init_switches_ruby_on_cd() {
+    let mut test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.5");
+    test.create_ruby_dir("ruby-3.4.1");
+    test.env
+        .insert("RV_TEST_EXE".into(), env!("CARGO_BIN_EXE_rv").into());
+    test.env.insert("PATH".into(), "/usr/bin:/bin".into());
+
+    fs_err::write(test.cwd.join(".ruby-version"), "3.4.1\n").unwrap();
+    let project = test.cwd.join("project");
+    fs_err::create_dir(&project).unwrap();
+    fs_err::write(project.join(".ruby-version"), "3.3.5\n").unwrap();
+
+    // Bash only runs PROMPT_COMMAND when it's interactive, so run it as if in a terminal.
+    let output = test.shell(
+        "bash",
+        &["--norc", "--noprofile", "-i"],
+        indoc! {r#"
+            eval "$("$RV_TEST_EXE" shell init bash)"
+            echo "$RUBY_VERSION"
+            cd project
+            echo "$RUBY_VERSION"
+            cd ..
+            echo "$RUBY_VERSION"
+            eval "$("$RV_TEST_EXE" shell init bash)"
+            echo "$PROMPT_COMMAND"
+        "#},
+    );
+    output.assert_success();
+
+    assert_eq!(output.stdout(), "3.4.1\n3.3.5\n3.4.1\n_chpwd_hook\n");
+}
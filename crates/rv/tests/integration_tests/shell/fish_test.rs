@@ -0,0 +1,46 @@
+use crate::common::RvTest;
+use indoc::indoc;
+use std::process::Command;
+
+#[test]
+fn test_fish_init_switches_ruby_on_cd() {
+    // Not every machine running the tests has fish. Setting RV_TEST_FISH makes a
+    // missing fish fail the test instead of skipping it.
+    if Command::new("fish").arg("--version").output().is_err() {
+        assert!(
+            std::env::var_os("RV_TEST_FISH").is_none(),
+            "RV_TEST_FISH is set but fish is not on PATH"
+        );
+        eprintln!("skipping test_fish_init_switches_ruby_on_cd: fish is not on PATH");
+        return;
+    }
+
+    let mut test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.5");
+    test.create_ruby_dir("ruby-3.4.1");
+    test.env
+        .insert("RV_TEST_EXE".into(), env!("CARGO_BIN_EXE_rv").into());
+    test.env.insert("PATH".into(), "/usr/bin:/bin".into());
+
+    fs_err::write(test.cwd.join(".ruby-version"), "3.4.1\n").unwrap();
+    let project = test.cwd.join("project");
+    fs_err::create_dir(&project).unwrap();
+    fs_err::write(project.join(".ruby-version"), "3.3.5\n").unwrap();
+
+    // The mock Ruby prints its version on the second line. Nothing but `cd` changes it.
+    let output = test.shell(
+        "fish",
+        &["--no-config"],
+        indoc! {r#"
+            "$RV_TEST_EXE" shell init fish | source
+            ruby | sed -n 2p
+            cd project
+            ruby | sed -n 2p
+            cd ..
+            ruby | sed -n 2p
+        "#},
+    );
+    output.assert_success();
+
+    assert_eq!(output.stdout(), "3.4.1\n3.3.5\n3.4.1\n");
+}
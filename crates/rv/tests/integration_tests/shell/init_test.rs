@@ -19,6 +19,15 @@ fn test_nu_shell_init_succeeds() {
     assert_snapshot!(output.normalized_stdout());
 }
 
+#[test]
+fn test_fish_shell_init_succeeds() {
+    let test = RvTest::new();
+    let output = test.rv(&["shell", "init", "fish"]);
+    output.assert_success();
+
+    assert_snapshot!(output.normalized_stdout());
+}
+
 #[test]
 fn test_shell_init_fails_without_shell() {
     let test = RvTest::new();
@@ -1,2 +1,8 @@
+#[cfg(unix)]
+mod bash_test;
 mod env_test;
+#[cfg(unix)]
+mod fish_test;
 mod init_test;
+#[cfg(unix)]
+mod zsh_test;
@@ -0,0 +1,46 @@
+use crate::common::RvTest;
+use indoc::indoc;
+use std::process::Command;
+
+#[test]
+fn test_zsh_init_switches_ruby_on_cd() {
+    // Not every machine running the tests has zsh. Setting RV_TEST_ZSH makes a
+    // missing zsh fail the test instead of skipping it.
+    if Command::new("zsh").arg("--version").output().is_err() {
+        assert!(
+            std::env::var_os("RV_TEST_ZSH").is_none(),
+            "RV_TEST_ZSH is set but zsh is not on PATH"
+        );
+        eprintln!("skipping test_zsh_init_switches_ruby_on_cd: zsh is not on PATH");
+        return;
+    }
+
+    let mut test = RvTest::new();
+    test.create_ruby_dir("ruby-3.3.5");
+    test.create_ruby_dir("ruby-3.4.1");
+    test.env
+        .insert("RV_TEST_EXE".into(), env!("CARGO_BIN_EXE_rv").into());
+    test.env.insert("PATH".into(), "/usr/bin:/bin".into());
+
+    fs_err::write(test.cwd.join(".ruby-version"), "3.4.1\n").unwrap();
+    let project = test.cwd.join("project");
+    fs_err::create_dir(&project).unwrap();
+    fs_err::write(project.join(".ruby-version"), "3.3.5\n").unwrap();
+
+    // The mock Ruby prints its version on the second line. Nothing but `cd` changes it.
+    let output = test.shell(
+        "zsh",
+        &["-f"],
+        indoc! {r#"
+            eval "$("$RV_TEST_EXE" shell init zsh)"
+            ruby | sed -n 2p
+            cd project
+            ruby | sed -n 2p
+            cd ..
+            ruby | sed -n 2p
+        "#},
+    );
+    output.assert_success();
+
+    assert_eq!(output.stdout(), "3.4.1\n3.3.5\n3.4.1\n");
+}
@@ -0,0 +1,50 @@
+use crate::common::{RvOutput, RvTest};
+
+const LOCKFILE: &str = include_str!("../../../../rv-lockfile/tests/inputs/Gemfile.lock.test0");
+
+impl RvTest {
+    pub fn why(&self, gem: &str) -> RvOutput {
+        let mut cmd = self.rv_command();
+        cmd.args(["why", gem]);
+
+        let output = cmd.output().expect("Failed to execute rv command");
+        RvOutput::new(self.temp_dir.path().as_str(), output)
+    }
+}
+
+fn setup() -> RvTest {
+    let test = RvTest::new();
+    std::fs::write(test.cwd.join("Gemfile.lock"), LOCKFILE).unwrap();
+    test
+}
+
+#[test]
+fn test_why_transitive_dependency() {
+    let test = setup();
+
+    let output = test.why("sorbet-static");
+    output.assert_success();
+    assert_eq!(
+        output.normalized_stdout(),
+        "tapioca > sorbet-static-and-runtime > sorbet > sorbet-static\n\
+         tapioca > spoom > sorbet-static-and-runtime > sorbet > sorbet-static\n"
+    );
+}
+
+#[test]
+fn test_why_direct_dependency() {
+    let test = setup();
+
+    let output = test.why("tapioca");
+    output.assert_success();
+    assert_eq!(output.normalized_stdout(), "tapioca is in the Gemfile\n");
+}
+
+#[test]
+fn test_why_gem_not_locked() {
+    let test = setup();
+
+    let output = test.why("rails");
+    output.assert_failure();
+    assert!(output.normalized_stderr().contains("NotLocked"));
+}